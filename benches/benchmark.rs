@@ -28,11 +28,7 @@ fn common_pw_list_reader_benchmark(c: &mut Criterion) {
     // Benchmark for how fast PasswordReader reads every password from the common passwords list.
     // This only benchmarks the actual reading, it does not benchmark and kind of password attempt.
     c.bench_function("Password Reader | Common PW list", |b| {
-        b.iter_batched(
-            || create_reader(),
-            |reader| read_pw_list(reader),
-            BatchSize::SmallInput,
-        );
+        b.iter_batched(create_reader, read_pw_list, BatchSize::SmallInput);
     });
 }
 
@@ -45,14 +41,14 @@ fn crack_password(safe_cracker: SafeCracker) {
     black_box(result);
 }
 
-fn create_safe_cracker() -> SafeCracker<'static> {
+fn create_safe_cracker() -> SafeCracker {
     SafeCracker::build(Options::new(
         true,
         true,
         Some(CommonPwListOptions::build(Some(100_000)).unwrap()),
         false,
         None,
-        None,
+        vec![],
     ))
     .unwrap()
 }
@@ -60,11 +56,7 @@ fn create_safe_cracker() -> SafeCracker<'static> {
 fn common_pw_list_safe_cracker_benchmark(c: &mut Criterion) {
     // Benchmark for how fast SafeCracker goes through 1M passwords.
     c.bench_function("Safe Cracker | Common PW list, 1 μs delay", |b| {
-        b.iter_batched(
-            || create_safe_cracker(),
-            |safe_cracker| crack_password(safe_cracker),
-            BatchSize::SmallInput,
-        );
+        b.iter_batched(create_safe_cracker, crack_password, BatchSize::SmallInput);
     });
 }
 
@@ -0,0 +1,39 @@
+//! Compresses the common password list into `OUT_DIR`, so it can be embedded in the library.
+
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(embedded_common_passwords)");
+
+    #[cfg(feature = "embedded-common-passwords")]
+    embed_common_passwords();
+}
+
+#[cfg(feature = "embedded-common-passwords")]
+fn embed_common_passwords() {
+    use std::fs::File;
+    use std::io::{self, BufReader};
+    use std::path::Path;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    const COMMON_PW_PATH: &str = "resources/common-passwords.txt";
+
+    // A missing file is checked again on every build, so it is embedded as soon as it is added.
+    println!("cargo:rerun-if-changed={COMMON_PW_PATH}");
+
+    if !Path::new(COMMON_PW_PATH).is_file() {
+        // Building should not fail because of the list, SafeCracker falls back to reading it from disk.
+        println!(
+            "cargo:warning={COMMON_PW_PATH} was not found, the common password list will not be embedded."
+        );
+        return;
+    }
+
+    let out_path = Path::new(&std::env::var("OUT_DIR").unwrap()).join("common-passwords.txt.gz");
+    let mut input = BufReader::new(File::open(COMMON_PW_PATH).unwrap());
+    let mut encoder = GzEncoder::new(File::create(out_path).unwrap(), Compression::best());
+    io::copy(&mut input, &mut encoder).unwrap();
+    encoder.finish().unwrap();
+
+    println!("cargo:rustc-cfg=embedded_common_passwords");
+}
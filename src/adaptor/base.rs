@@ -1,15 +1,42 @@
 //! Module that contains the `BaseAdaptor`.
 
-use std::error::Error;
-
 use crate::adaptor::attempt_result::AttemptResult;
+use crate::error::RamenskyError;
 
 /// The base adaptor trait for all adaptors.
-/// For an example of an adaptor, see [`ZipAdaptor`](crate::adaptor::custom::zip_adaptor::ZipAdaptor).
+/// Passwords are always valid UTF-8. To try passwords as bytes, implement
+/// [`ByteAdaptor`](crate::adaptor::byte_adaptor::ByteAdaptor) instead.
 pub trait BaseAdaptor {
     /// This method will be called for every password that has been generated.
     /// If the result is ```SafeCrackResult::Success```, execution will end.
     /// If the result is ```SafeCrackResult::Failure```, execution will continue, until all passwords
     /// are exhausted.
-    fn try_password(&self, password: &String) -> Result<AttemptResult, Box<dyn Error>>;
+    /// Errors of the target can be wrapped with [`RamenskyError::adaptor()`]. Whether they stop the execution
+    /// is decided by the [`error_policy`](crate::safe_cracker::options::Options::error_policy).
+    /// Returning [`RamenskyError::Cancelled`] interrupts the execution instead.
+    fn try_password(&self, password: &str) -> Result<AttemptResult, RamenskyError>;
+
+    /// This method will be called for every batch of passwords, if
+    /// [`batch_size`](crate::safe_cracker::options::Options::batch_size) is more than 1.
+    /// Returns the index of the correct password, or [`None`](Option::None) if none of them are correct.
+    /// Override it for targets that can check many passwords more cheaply together, e.g. because of a shared
+    /// setup, or one process for the whole batch. By default, the passwords are tried one by one with
+    /// [`try_password()`](BaseAdaptor::try_password), until the correct one is found.
+    fn try_passwords(&self, passwords: &[&str]) -> Result<Option<usize>, RamenskyError> {
+        for (index, password) in passwords.iter().enumerate() {
+            if let AttemptResult::Success = self.try_password(password)? {
+                return Ok(Some(index));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// A stable identifier of the target, e.g. a hash of the encrypted file, used to look up passwords
+    /// that were already found in the [`potfile`](crate::safe_cracker::options::Options::potfile_path).
+    /// Needs to stay the same across runs, and cannot contain tabs or line breaks.
+    /// Targets without a fingerprint, which is the default, are not looked up or saved.
+    fn fingerprint(&self) -> Option<String> {
+        None
+    }
 }
@@ -0,0 +1,111 @@
+//! Module that contains the `ByteAdaptor`.
+
+use crate::adaptor::attempt_result::AttemptResult;
+use crate::adaptor::base::BaseAdaptor;
+use crate::error::RamenskyError;
+
+/// Adaptor trait for targets that take passwords as bytes, e.g. legacy encoded archives or hashes.
+/// Passwords are passed as they were generated, so they are not necessarily valid UTF-8, see
+/// [`EncodingPolicy::Raw`](crate::safe_cracker::encoding_policy::EncodingPolicy::Raw).
+///
+/// Every [`BaseAdaptor`] is a `ByteAdaptor` as well. Passwords that are not valid UTF-8 are not passed to
+/// them, and are skipped like with [`EncodingPolicy::Skip`](crate::safe_cracker::encoding_policy::EncodingPolicy::Skip),
+/// so they do not count as attempts.
+/// For an example of a byte adaptor, see [`ZipAdaptor`](crate::adaptor::custom::zip_adaptor::ZipAdaptor).
+pub trait ByteAdaptor {
+    /// This method will be called for every password that has been generated, like
+    /// [`BaseAdaptor::try_password()`]. The password is only borrowed for the attempt, so it does not need
+    /// to be allocated for every attempt.
+    fn try_password_bytes(&self, password: &[u8]) -> Result<AttemptResult, RamenskyError>;
+
+    /// This method will be called for every batch of passwords, like [`BaseAdaptor::try_passwords()`].
+    /// By default, the passwords are tried one by one with [`try_password_bytes()`](ByteAdaptor::try_password_bytes).
+    fn try_passwords_bytes(&self, passwords: &[&[u8]]) -> Result<Option<usize>, RamenskyError> {
+        for (index, password) in passwords.iter().enumerate() {
+            if let AttemptResult::Success = self.try_password_bytes(password)? {
+                return Ok(Some(index));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// A stable identifier of the target, like [`BaseAdaptor::fingerprint()`].
+    fn fingerprint(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether passwords that are not valid UTF-8 can be tried. If not, they are skipped before they are
+    /// handed to the adaptor, and counted in
+    /// [`skipped_lines`](crate::safe_cracker::statistics::Statistics::skipped_lines) instead of the attempts.
+    fn accepts_raw_passwords(&self) -> bool {
+        true
+    }
+}
+
+impl<T: BaseAdaptor + ?Sized> ByteAdaptor for T {
+    fn try_password_bytes(&self, password: &[u8]) -> Result<AttemptResult, RamenskyError> {
+        match std::str::from_utf8(password) {
+            Ok(password) => self.try_password(password),
+            Err(_) => Ok(AttemptResult::Failure),
+        }
+    }
+
+    fn try_passwords_bytes(&self, passwords: &[&[u8]]) -> Result<Option<usize>, RamenskyError> {
+        // Index in `passwords` of every password in the batch, as not every password is valid UTF-8.
+        let mut indexes = vec![];
+        let mut batch = vec![];
+
+        for (index, password) in passwords.iter().enumerate() {
+            if let Ok(password) = std::str::from_utf8(password) {
+                indexes.push(index);
+                batch.push(password);
+            }
+        }
+
+        if batch.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(self.try_passwords(&batch)?.map(|index| indexes[index]))
+    }
+
+    fn fingerprint(&self) -> Option<String> {
+        BaseAdaptor::fingerprint(self)
+    }
+
+    fn accepts_raw_passwords(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adaptor::attempt_result::AttemptResult;
+    use crate::adaptor::byte_adaptor::ByteAdaptor;
+    use crate::adaptor::custom::test_adaptor::TestAdaptor;
+
+    #[test]
+    fn can_bridge_base_adaptors() {
+        let adaptor = TestAdaptor::without_delay("café");
+
+        assert!(matches!(
+            adaptor.try_password_bytes("café".as_bytes()),
+            Ok(AttemptResult::Success)
+        ));
+        assert!(matches!(
+            adaptor.try_password_bytes(b"caf\xe9"),
+            Ok(AttemptResult::Failure)
+        ));
+        assert!(!adaptor.accepts_raw_passwords());
+    }
+
+    #[test]
+    fn can_bridge_batches() {
+        let adaptor = TestAdaptor::without_delay("café");
+        let batch: [&[u8]; 3] = [b"caf\xe9", b"test", "café".as_bytes()];
+
+        assert_eq!(adaptor.try_passwords_bytes(&batch).unwrap(), Some(2));
+        assert_eq!(adaptor.try_passwords_bytes(&batch[..2]).unwrap(), None);
+    }
+}
@@ -0,0 +1,175 @@
+//! CommandAdaptor is an adaptor that runs a command for every password, and checks its exit code.
+//! It can be used for any target that has a command-line tool, e.g. `7z t -p{} archive.7z`.
+//! The password is passed in place of every `{}` in the arguments, or written to the standard input
+//! of the command if none of the arguments contain `{}`.
+
+use std::ffi::OsString;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::adaptor::attempt_result::AttemptResult;
+use crate::adaptor::byte_adaptor::ByteAdaptor;
+use crate::error::RamenskyError;
+
+/// Placeholder in the arguments that is replaced with the password.
+pub const PLACEHOLDER: &str = "{}";
+
+pub struct CommandAdaptor {
+    program: String,
+    args: Vec<String>,
+
+    /// Exit code of the command when the password is correct. Every other exit code is a failed attempt.
+    success_code: i32,
+}
+
+impl CommandAdaptor {
+    /// Build a `CommandAdaptor` that runs `program` with `args`. The password is correct if the command
+    /// exits with `success_code`.
+    pub fn build(program: &str, args: &[&str], success_code: i32) -> Result<Self, RamenskyError> {
+        if program.is_empty() {
+            return Err(RamenskyError::InvalidConfiguration(
+                "The program of the command cannot be empty.".to_string(),
+            ));
+        }
+
+        Ok(CommandAdaptor {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            success_code,
+        })
+    }
+
+    /// Whether the password is passed in the arguments, instead of the standard input.
+    fn uses_placeholder(&self) -> bool {
+        self.args.iter().any(|arg| arg.contains(PLACEHOLDER))
+    }
+}
+
+impl ByteAdaptor for CommandAdaptor {
+    fn try_password_bytes(&self, password: &[u8]) -> Result<AttemptResult, RamenskyError> {
+        let mut command = Command::new(&self.program);
+        command.stdout(Stdio::null()).stderr(Stdio::null());
+
+        let uses_placeholder = self.uses_placeholder();
+
+        if uses_placeholder {
+            for arg in &self.args {
+                match substitute(arg, password) {
+                    Some(arg) => command.arg(arg),
+                    // Arguments of this platform cannot contain the password, so it cannot be tried.
+                    None => return Ok(AttemptResult::Failure),
+                };
+            }
+            command.stdin(Stdio::null());
+        } else {
+            command.args(&self.args).stdin(Stdio::piped());
+        }
+
+        let mut child = command.spawn().map_err(RamenskyError::adaptor)?;
+
+        if !uses_placeholder {
+            if let Some(mut stdin) = child.stdin.take() {
+                // The command can exit without reading its input, so a broken pipe is not an error.
+                let _ = stdin
+                    .write_all(password)
+                    .and_then(|_| stdin.write_all(b"\n"));
+            }
+        }
+
+        let status = child.wait().map_err(RamenskyError::adaptor)?;
+
+        match status.code() {
+            Some(code) if code == self.success_code => Ok(AttemptResult::Success),
+            Some(_) => Ok(AttemptResult::Failure),
+            None => Err(RamenskyError::adaptor(format!(
+                "{} was terminated by a signal.",
+                self.program
+            ))),
+        }
+    }
+}
+
+/// Replace every placeholder in `arg` with the password. Returns [`None`] if the password cannot be
+/// an argument, which is only the case for passwords that are not valid UTF-8 outside of Unix.
+fn substitute(arg: &str, password: &[u8]) -> Option<OsString> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+
+        let mut bytes = vec![];
+
+        for (index, part) in arg.split(PLACEHOLDER).enumerate() {
+            if index > 0 {
+                bytes.extend_from_slice(password);
+            }
+            bytes.extend_from_slice(part.as_bytes());
+        }
+
+        Some(OsString::from_vec(bytes))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let password = std::str::from_utf8(password).ok()?;
+        Some(OsString::from(arg.replace(PLACEHOLDER, password)))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use crate::adaptor::attempt_result::AttemptResult;
+    use crate::adaptor::byte_adaptor::ByteAdaptor;
+    use crate::adaptor::custom::command_adaptor::CommandAdaptor;
+
+    fn try_command(args: &[&str], password: &[u8]) -> AttemptResult {
+        CommandAdaptor::build("sh", args, 0)
+            .unwrap()
+            .try_password_bytes(password)
+            .unwrap()
+    }
+
+    #[test]
+    fn can_pass_password_as_argument() {
+        let args = ["-c", "test \"$1\" = 'pass word'", "sh", "{}"];
+
+        assert!(matches!(
+            try_command(&args, b"pass word"),
+            AttemptResult::Success
+        ));
+        assert!(matches!(
+            try_command(&args, b"pass"),
+            AttemptResult::Failure
+        ));
+    }
+
+    #[test]
+    fn can_pass_password_to_stdin() {
+        let args = ["-c", "read pw; test \"$pw\" = test"];
+
+        assert!(matches!(
+            try_command(&args, b"test"),
+            AttemptResult::Success
+        ));
+        assert!(matches!(
+            try_command(&args, b"tset"),
+            AttemptResult::Failure
+        ));
+    }
+
+    #[test]
+    fn can_use_success_code() {
+        let adaptor = CommandAdaptor::build("sh", &["-c", "exit 3"], 3).unwrap();
+        assert!(matches!(
+            adaptor.try_password_bytes(b"test").unwrap(),
+            AttemptResult::Success
+        ));
+    }
+
+    #[test]
+    fn should_fail_for_missing_program() {
+        let adaptor = CommandAdaptor::build("ramensky-missing-program", &[], 0).unwrap();
+        assert!(adaptor.try_password_bytes(b"test").is_err());
+
+        assert!(CommandAdaptor::build("", &[], 0).is_err());
+    }
+}
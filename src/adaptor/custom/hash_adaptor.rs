@@ -0,0 +1,369 @@
+//! HashAdaptor is an adaptor for cracking raw password hashes offline, e.g. from a database dump.
+//! Every password is hashed with the algorithm of the target digest, and compared to it. MD5, SHA-1,
+//! SHA-256, SHA-512 and NTLM are supported, optionally with a salt before or after the password.
+//! Hashes are computed over bytes, so it is a [`ByteAdaptor`], and can crack hashes of passwords that
+//! were not encoded as UTF-8.
+//!
+//! ```rust
+//! use ramensky::adaptor::attempt_result::AttemptResult;
+//! use ramensky::adaptor::byte_adaptor::ByteAdaptor;
+//! use ramensky::adaptor::custom::hash_adaptor::{
+//!     DigestFormat, HashAdaptor, HashAlgorithm, SaltPosition,
+//! };
+//!
+//! // SHA-256 of "salt" followed by "password".
+//! let adaptor = HashAdaptor::build(
+//!     HashAlgorithm::Sha256,
+//!     "13601bda4ea78e55a07b98866d2be6be0744e3866f13c00c811cab608a28f322",
+//!     DigestFormat::Hex,
+//! )
+//! .unwrap()
+//! .with_salt("salt", SaltPosition::Before);
+//!
+//! assert!(matches!(
+//!     adaptor.try_password_bytes(b"password"),
+//!     Ok(AttemptResult::Success)
+//! ));
+//! ```
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use md4::Md4;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::adaptor::attempt_result::AttemptResult;
+use crate::adaptor::byte_adaptor::ByteAdaptor;
+use crate::error::RamenskyError;
+use crate::hex;
+
+/// The hash function of the target digest.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    /// MD4 of the password encoded as UTF-16LE, used by Windows. Passwords that are not valid UTF-8
+    /// cannot be encoded, and count as failed attempts.
+    Ntlm,
+}
+
+impl HashAlgorithm {
+    /// Size of the digests of the algorithm, in bytes.
+    pub fn digest_size(&self) -> usize {
+        match self {
+            HashAlgorithm::Md5 | HashAlgorithm::Ntlm => 16,
+            HashAlgorithm::Sha1 => 20,
+            HashAlgorithm::Sha256 => 32,
+            HashAlgorithm::Sha512 => 64,
+        }
+    }
+
+    /// Whether the digest of `input` is `digest`, or [`None`] if the input cannot be hashed.
+    fn matches(&self, input: &[u8], digest: &[u8]) -> Option<bool> {
+        let matches = match self {
+            HashAlgorithm::Md5 => Md5::digest(input).as_slice() == digest,
+            HashAlgorithm::Sha1 => Sha1::digest(input).as_slice() == digest,
+            HashAlgorithm::Sha256 => Sha256::digest(input).as_slice() == digest,
+            HashAlgorithm::Sha512 => Sha512::digest(input).as_slice() == digest,
+            HashAlgorithm::Ntlm => {
+                let mut hasher = Md4::new();
+
+                for unit in std::str::from_utf8(input).ok()?.encode_utf16() {
+                    hasher.update(unit.to_le_bytes());
+                }
+
+                hasher.finalize().as_slice() == digest
+            }
+        };
+
+        Some(matches)
+    }
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Ntlm => "ntlm",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = RamenskyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "").as_str() {
+            "md5" => Ok(HashAlgorithm::Md5),
+            "sha1" => Ok(HashAlgorithm::Sha1),
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            "ntlm" => Ok(HashAlgorithm::Ntlm),
+            _ => Err(RamenskyError::InvalidConfiguration(format!(
+                "Unknown hash algorithm {s}."
+            ))),
+        }
+    }
+}
+
+/// How the target digest is written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DigestFormat {
+    /// Hexadecimal, in lower or upper case, e.g. `5f4dcc3b5aa765d61d8327deb882cf99`.
+    Hex,
+    /// Standard Base64, with padding, e.g. `X03MO1qnZdYdgyfeuILPmQ==`.
+    Base64,
+}
+
+/// Where the salt is added to the password before it is hashed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SaltPosition {
+    /// `hash(salt + password)`.
+    Before,
+    /// `hash(password + salt)`.
+    After,
+}
+
+pub struct HashAdaptor {
+    algorithm: HashAlgorithm,
+
+    /// The target digest, decoded.
+    digest: Vec<u8>,
+
+    /// Salt added to every password, empty if the hash is not salted.
+    salt: Vec<u8>,
+    salt_position: SaltPosition,
+}
+
+impl HashAdaptor {
+    /// Build a `HashAdaptor` for the target `digest`, written in the given format. Fails if the digest
+    /// cannot be decoded, or its size does not match the algorithm.
+    pub fn build(
+        algorithm: HashAlgorithm,
+        digest: &str,
+        format: DigestFormat,
+    ) -> Result<Self, RamenskyError> {
+        let invalid = || {
+            RamenskyError::InvalidConfiguration(format!(
+                "Digest {digest} is not a valid {algorithm} digest."
+            ))
+        };
+
+        let digest_bytes = match format {
+            DigestFormat::Hex => hex::decode(digest.trim()),
+            DigestFormat::Base64 => STANDARD.decode(digest.trim()).ok(),
+        }
+        .ok_or_else(invalid)?;
+
+        if digest_bytes.len() != algorithm.digest_size() {
+            return Err(invalid());
+        }
+
+        Ok(HashAdaptor {
+            algorithm,
+            digest: digest_bytes,
+            salt: vec![],
+            salt_position: SaltPosition::After,
+        })
+    }
+
+    /// Add the salt to every password before it is hashed, at the given position.
+    pub fn with_salt(mut self, salt: impl Into<Vec<u8>>, position: SaltPosition) -> Self {
+        self.salt = salt.into();
+        self.salt_position = position;
+        self
+    }
+}
+
+impl ByteAdaptor for HashAdaptor {
+    fn try_password_bytes(&self, password: &[u8]) -> Result<AttemptResult, RamenskyError> {
+        let matches = match (self.salt.is_empty(), self.salt_position) {
+            (true, _) => self.algorithm.matches(password, &self.digest),
+            (false, SaltPosition::Before) => self
+                .algorithm
+                .matches(&[self.salt.as_slice(), password].concat(), &self.digest),
+            (false, SaltPosition::After) => self
+                .algorithm
+                .matches(&[password, self.salt.as_slice()].concat(), &self.digest),
+        };
+
+        match matches {
+            Some(true) => Ok(AttemptResult::Success),
+            _ => Ok(AttemptResult::Failure),
+        }
+    }
+
+    /// The algorithm and the digest, followed by the salt and its position if the hash is salted.
+    fn fingerprint(&self) -> Option<String> {
+        let mut fingerprint = format!("{}:{}", self.algorithm, hex::encode(&self.digest));
+
+        if !self.salt.is_empty() {
+            let position = match self.salt_position {
+                SaltPosition::Before => "before",
+                SaltPosition::After => "after",
+            };
+            fingerprint.push_str(&format!(":{position}:{}", hex::encode(&self.salt)));
+        }
+
+        Some(fingerprint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adaptor::attempt_result::AttemptResult;
+    use crate::adaptor::byte_adaptor::ByteAdaptor;
+    use crate::adaptor::custom::hash_adaptor::{
+        DigestFormat, HashAdaptor, HashAlgorithm, SaltPosition,
+    };
+
+    fn is_found(adaptor: &HashAdaptor, password: &[u8]) -> bool {
+        matches!(
+            adaptor.try_password_bytes(password).unwrap(),
+            AttemptResult::Success
+        )
+    }
+
+    #[test]
+    fn can_crack_hashes() {
+        let digests = [
+            (HashAlgorithm::Md5, "5f4dcc3b5aa765d61d8327deb882cf99"),
+            (
+                HashAlgorithm::Sha1,
+                "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8",
+            ),
+            (
+                HashAlgorithm::Sha256,
+                "5e884898da28047151d0e56f8dc6292773603d0d6aabbdd62a11ef721d1542d8",
+            ),
+            (
+                HashAlgorithm::Sha512,
+                "b109f3bbbc244eb82441917ed06d618b9008dd09b3befd1b5e07394c706a8bb9\
+                 80b1d7785e5976ec049b46df5f1326af5a2ea6d103fd07c95385ffab0cacbc86",
+            ),
+            (HashAlgorithm::Ntlm, "8846f7eaee8fb117ad06bdd830b7586c"),
+        ];
+
+        for (algorithm, digest) in digests {
+            let adaptor = HashAdaptor::build(algorithm, digest, DigestFormat::Hex).unwrap();
+
+            assert!(is_found(&adaptor, b"password"), "{algorithm}");
+            assert!(!is_found(&adaptor, b"Password"), "{algorithm}");
+            assert!(!is_found(&adaptor, b"\xff"), "{algorithm}");
+        }
+    }
+
+    #[test]
+    fn can_crack_base64_hashes() {
+        let adaptor = HashAdaptor::build(
+            HashAlgorithm::Sha1,
+            "W6ph5Mm5Pz8GgiULbPgzG37mj9g=",
+            DigestFormat::Base64,
+        )
+        .unwrap();
+
+        assert!(is_found(&adaptor, b"password"));
+    }
+
+    #[test]
+    fn can_crack_salted_hashes() {
+        let before = HashAdaptor::build(
+            HashAlgorithm::Sha256,
+            "13601bda4ea78e55a07b98866d2be6be0744e3866f13c00c811cab608a28f322",
+            DigestFormat::Hex,
+        )
+        .unwrap()
+        .with_salt("salt", SaltPosition::Before);
+
+        assert!(is_found(&before, b"password"));
+        assert!(!is_found(&before, b"saltpassword"));
+
+        let after = HashAdaptor::build(
+            HashAlgorithm::Md5,
+            "b305cadbb3bce54f3aa59c64fec00dea",
+            DigestFormat::Hex,
+        )
+        .unwrap()
+        .with_salt("salt", SaltPosition::After);
+
+        assert!(is_found(&after, b"password"));
+        assert!(!is_found(&after, b"passwordsalt"));
+    }
+
+    #[test]
+    fn can_fingerprint() {
+        let adaptor = |salt: &str| {
+            HashAdaptor::build(
+                HashAlgorithm::Md5,
+                "5F4DCC3B5AA765D61D8327DEB882CF99",
+                DigestFormat::Hex,
+            )
+            .unwrap()
+            .with_salt(salt, SaltPosition::After)
+        };
+
+        assert_eq!(
+            adaptor("").fingerprint().unwrap(),
+            "md5:5f4dcc3b5aa765d61d8327deb882cf99"
+        );
+        assert_eq!(
+            adaptor("ab").fingerprint().unwrap(),
+            "md5:5f4dcc3b5aa765d61d8327deb882cf99:after:6162"
+        );
+    }
+
+    #[test]
+    fn should_validate_digest() {
+        let invalid = [
+            (
+                HashAlgorithm::Md5,
+                "5f4dcc3b5aa765d61d8327deb882cf9",
+                DigestFormat::Hex,
+            ),
+            (
+                HashAlgorithm::Md5,
+                "5f4dcc3b5aa765d61d8327deb882cfzz",
+                DigestFormat::Hex,
+            ),
+            (
+                HashAlgorithm::Md5,
+                "5f4dcc3b5aa765d61d8327deb882cf+f",
+                DigestFormat::Hex,
+            ),
+            (
+                HashAlgorithm::Sha256,
+                "5f4dcc3b5aa765d61d8327deb882cf99",
+                DigestFormat::Hex,
+            ),
+            (
+                HashAlgorithm::Sha1,
+                "W6ph5Mm5Pz8GgiULbPgzG37mj9g",
+                DigestFormat::Base64,
+            ),
+        ];
+
+        for (algorithm, digest, format) in invalid {
+            assert!(
+                HashAdaptor::build(algorithm, digest, format).is_err(),
+                "{digest}"
+            );
+        }
+
+        assert_eq!(
+            "SHA-256".parse::<HashAlgorithm>().unwrap(),
+            HashAlgorithm::Sha256
+        );
+        assert!("sha3".parse::<HashAlgorithm>().is_err());
+    }
+}
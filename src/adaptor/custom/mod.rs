@@ -1,3 +1,5 @@
 //! Module for custom adaptors, ready to use.
+pub mod command_adaptor;
+pub mod hash_adaptor;
 pub mod test_adaptor;
 pub mod zip_adaptor;
@@ -1,12 +1,12 @@
 //! TestAdaptor is a simple adaptor for testing and benchmarking purposes. It mocks how a normal Adaptor might work.
 //! It sleeps for a certain amount, checks whether the guessed password is correct, and returns the result.
 
-use std::error::Error;
 use std::thread;
 use std::time::Duration;
 
 use crate::adaptor::attempt_result::AttemptResult;
 use crate::adaptor::base::BaseAdaptor;
+use crate::error::RamenskyError;
 
 pub struct TestAdaptor<'a> {
     password: &'a str,
@@ -24,7 +24,7 @@ impl<'a> TestAdaptor<'a> {
 }
 
 impl<'a> BaseAdaptor for TestAdaptor<'a> {
-    fn try_password(&self, password: &String) -> Result<AttemptResult, Box<dyn Error>> {
+    fn try_password(&self, password: &str) -> Result<AttemptResult, RamenskyError> {
         thread::sleep(self.delay);
 
         if password == self.password {
@@ -47,7 +47,7 @@ mod tests {
         let adaptor = TestAdaptor::without_delay("abcde");
 
         matches!(
-            adaptor.try_password(&String::from("abcde")).unwrap(),
+            adaptor.try_password("abcde").unwrap(),
             AttemptResult::Success
         );
     }
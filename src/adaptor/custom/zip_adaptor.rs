@@ -1,83 +1,276 @@
 //! ZipAdaptor is an adaptor for decrypting password protected zip archives.
-//! It uses `unzip` to try to unlock the file.
+//! It opens the archive once, and tries every password in-process, supporting both ZipCrypto and
+//! WinZip AES encryption. The archive is only extracted once the password is found.
+//! Zip passwords are bytes, so it is a [`ByteAdaptor`], and can try passwords of archives that were
+//! encrypted with a legacy encoding.
 
-use std::error::Error;
-use std::process::Command;
-use std::str;
+use std::fs;
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+use zip::result::ZipError;
+use zip::ZipArchive;
 
 use crate::adaptor::attempt_result::AttemptResult;
-use crate::adaptor::base::BaseAdaptor;
+use crate::adaptor::byte_adaptor::ByteAdaptor;
+use crate::error::RamenskyError;
+use crate::hex;
 
 pub struct ZipAdaptor<'a> {
-    /// Path to the zip file that is encrpyted.
-    zip_path: &'a str,
-
     /// Where to extract the file, if the password is found. Needs to be a directory.
     /// If it doesn't exist, it will be created.
     extract_path: &'a str,
+
+    /// The encrypted archive, read into memory once. Cloning it is cheap, as the contents are shared.
+    archive: ZipArchive<Cursor<Arc<[u8]>>>,
+
+    /// Index of the encrypted file in the archive that passwords are tried against.
+    file_index: usize,
+
+    /// SHA-256 of the encrypted file, so the same archive is recognised in the potfile.
+    fingerprint: String,
 }
 
 impl<'a> ZipAdaptor<'a> {
-    pub fn new(zip_path: &'a str, extract_path: &'a str) -> Self {
-        Self {
-            zip_path,
-            extract_path,
+    /// Build a `ZipAdaptor` for the zip file at `zip_path`. Fails if the file is not a valid zip archive,
+    /// or none of its files are encrypted.
+    pub fn build(zip_path: &str, extract_path: &'a str) -> Result<Self, RamenskyError> {
+        let contents: Arc<[u8]> = fs::read(zip_path)
+            .map_err(|e| RamenskyError::io(zip_path, e))?
+            .into();
+        let mut archive = ZipArchive::new(Cursor::new(contents)).map_err(RamenskyError::adaptor)?;
+
+        // Every attempt decrypts the whole file, so the smallest encrypted one is the fastest to check.
+        let mut file_index = None;
+        let mut smallest_size = u64::MAX;
+
+        for index in 0..archive.len() {
+            let encrypted = matches!(
+                archive.by_index(index),
+                Err(ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED))
+            );
+
+            if !encrypted {
+                continue;
+            }
+
+            let size = archive
+                .by_index_raw(index)
+                .map_err(RamenskyError::adaptor)?
+                .compressed_size();
+
+            if size < smallest_size {
+                smallest_size = size;
+                file_index = Some(index);
+            }
+        }
+
+        match file_index {
+            Some(file_index) => Ok(Self {
+                extract_path,
+                fingerprint: fingerprint(&mut archive, file_index)?,
+                archive,
+                file_index,
+            }),
+            None => Err(RamenskyError::InvalidConfiguration(format!(
+                "Zip file {zip_path} is not password protected."
+            ))),
+        }
+    }
+
+    /// Extract every file that can be decrypted with the password. Files of the archive can have different
+    /// passwords, so the files that cannot be decrypted are skipped, instead of failing the attempt.
+    /// Called once the password is found, or directly for a password that is already known, e.g. from the
+    /// [`potfile`](crate::safe_cracker::options::Options::potfile_path), which is not tried again.
+    pub fn extract(&self, password: &[u8]) -> Result<(), RamenskyError> {
+        let mut archive = self.archive.clone();
+        let extract_path = Path::new(self.extract_path);
+
+        for index in 0..archive.len() {
+            let mut file = match archive
+                .by_index_decrypt(index, password)
+                .map_err(RamenskyError::adaptor)?
+            {
+                Ok(file) => file,
+                Err(_) => continue,
+            };
+
+            // Skip files that would be extracted outside of the extract path.
+            let path = match file.enclosed_name() {
+                Some(name) => extract_path.join(name),
+                None => continue,
+            };
+
+            if file.is_dir() {
+                create_dir_all(&path)?;
+                continue;
+            }
+
+            // ZipCrypto lets some wrong passwords through, which is only noticed once the file is read.
+            let mut contents = vec![];
+            if file.read_to_end(&mut contents).is_err() {
+                continue;
+            }
+
+            if let Some(parent) = path.parent() {
+                create_dir_all(parent)?;
+            }
+
+            fs::write(&path, contents).map_err(|e| RamenskyError::io(&path, e))?;
         }
+
+        Ok(())
     }
 }
 
-impl<'a> BaseAdaptor for ZipAdaptor<'a> {
-    fn try_password(&self, password: &String) -> Result<AttemptResult, Box<dyn Error>> {
-        let command = format!(
-            "unzip -P {password} {} -d {}",
-            self.zip_path, self.extract_path
-        );
+impl<'a> ByteAdaptor for ZipAdaptor<'a> {
+    fn try_password_bytes(&self, password: &[u8]) -> Result<AttemptResult, RamenskyError> {
+        let mut archive = self.archive.clone();
 
-        let output = if cfg!(target_os = "windows") {
-            Command::new("cmd")
-                .args(["/C", command.as_str()])
-                .output()
-                .expect(format!("Failed to execute command {}", command.as_str()).as_str())
-        } else {
-            Command::new("sh")
-                .arg("-c")
-                .arg(command.as_str())
-                .output()
-                .expect(format!("Failed to execute command {}", command.as_str()).as_str())
+        let mut file = match archive
+            .by_index_decrypt(self.file_index, password)
+            .map_err(RamenskyError::adaptor)?
+        {
+            Ok(file) => file,
+            Err(_) => return Ok(AttemptResult::Failure),
         };
 
-        if output.status.success() {
-            Ok(AttemptResult::Success)
-        } else {
-            Ok(AttemptResult::Failure)
+        // ZipCrypto only checks a single byte of the password, so some wrong passwords get through.
+        // Reading the whole file verifies its checksum, or its authentication code for AES.
+        if io::copy(&mut file, &mut io::sink()).is_err() {
+            return Ok(AttemptResult::Failure);
         }
+
+        self.extract(password)?;
+
+        Ok(AttemptResult::Success)
+    }
+
+    fn fingerprint(&self) -> Option<String> {
+        Some(self.fingerprint.clone())
     }
 }
 
+/// Hash of the encrypted contents of the file at `file_index`, which do not change between runs.
+fn fingerprint(
+    archive: &mut ZipArchive<Cursor<Arc<[u8]>>>,
+    file_index: usize,
+) -> Result<String, RamenskyError> {
+    let mut file = archive
+        .by_index_raw(file_index)
+        .map_err(RamenskyError::adaptor)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).map_err(RamenskyError::adaptor)?;
+
+    Ok(format!("zip:{}", hex::encode(&hasher.finalize())))
+}
+
+fn create_dir_all(path: &Path) -> Result<(), RamenskyError> {
+    fs::create_dir_all(path).map_err(|e| RamenskyError::io(path, e))
+}
+
 #[cfg(test)]
 mod tests {
+    use std::env;
     use std::fs;
 
     use crate::adaptor::attempt_result::AttemptResult;
-    use crate::adaptor::base::BaseAdaptor;
+    use crate::adaptor::byte_adaptor::ByteAdaptor;
     use crate::adaptor::custom::zip_adaptor::ZipAdaptor;
 
+    fn try_unzip(zip_name: &str, password: &str) -> (AttemptResult, Option<String>) {
+        let zip_path = format!("{}/tests/resources/{zip_name}", env!("CARGO_MANIFEST_DIR"));
+        let extract_path = env::temp_dir().join(format!("ramensky-{zip_name}-{password}"));
+        let extract_path = extract_path.to_str().unwrap();
+
+        let zip_adaptor = ZipAdaptor::build(&zip_path, extract_path).unwrap();
+        let result = zip_adaptor.try_password_bytes(password.as_bytes()).unwrap();
+
+        let contents = fs::read_to_string(format!("{extract_path}/test")).ok();
+        let _ = fs::remove_dir_all(extract_path);
+
+        (result, contents)
+    }
+
     #[test]
     fn can_unzip() {
-        let zip_path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources/test.zip");
-        let extract_path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/out/");
+        // The password for the test.zip file is "test".
+        let (result, contents) = try_unzip("test.zip", "test");
 
-        let zip_adaptor = ZipAdaptor::new(zip_path, extract_path);
+        assert!(matches!(result, AttemptResult::Success));
+        assert_eq!(contents.as_deref(), Some("test\n"));
+    }
 
-        // The password for the test.zip file is "test".
-        let pw = String::from("test");
+    #[test]
+    fn can_unzip_aes() {
+        // test-aes.zip is encrypted with AES-256, the password is "test".
+        let (result, contents) = try_unzip("test-aes.zip", "test");
+
+        assert!(matches!(result, AttemptResult::Success));
+        assert_eq!(contents.as_deref(), Some("test\n"));
+    }
 
-        matches!(
-            zip_adaptor.try_password(&pw).unwrap(),
-            AttemptResult::Success
+    #[test]
+    fn can_unzip_with_mixed_passwords() {
+        // The "test" file of test-mixed.zip has the password "test", the "other" file has "other".
+        let zip_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/resources/test-mixed.zip"
         );
+        let extract_path = env::temp_dir().join("ramensky-test-mixed.zip");
+        let zip_adaptor = ZipAdaptor::build(zip_path, extract_path.to_str().unwrap()).unwrap();
+
+        let result = zip_adaptor.try_password_bytes(b"test").unwrap();
+        let contents = fs::read_to_string(extract_path.join("test")).ok();
+        let other_exists = extract_path.join("other").exists();
+        let _ = fs::remove_dir_all(&extract_path);
+
+        assert!(matches!(result, AttemptResult::Success));
+        assert_eq!(contents.as_deref(), Some("test\n"));
+        assert!(!other_exists);
+    }
+
+    #[test]
+    fn can_unzip_with_special_characters() {
+        let (result, contents) = try_unzip("test-special.zip", "pass word'\"");
+
+        assert!(matches!(result, AttemptResult::Success));
+        assert_eq!(contents.as_deref(), Some("test\n"));
+    }
+
+    #[test]
+    fn should_not_unzip_with_wrong_password() {
+        for zip_name in ["test.zip", "test-aes.zip", "test-special.zip"] {
+            for password in ["", "tset", "test; ls", "pass word"] {
+                let (result, contents) = try_unzip(zip_name, password);
+
+                assert!(matches!(result, AttemptResult::Failure));
+                assert_eq!(contents, None);
+            }
+        }
+    }
+
+    #[test]
+    fn can_fingerprint() {
+        let fingerprint = |zip_name| {
+            let zip_path = format!("{}/tests/resources/{zip_name}", env!("CARGO_MANIFEST_DIR"));
+            ZipAdaptor::build(&zip_path, "out").unwrap().fingerprint()
+        };
+
+        let fingerprint_zip = fingerprint("test.zip").unwrap();
+        assert!(fingerprint_zip.starts_with("zip:"));
+        assert_eq!(fingerprint("test.zip").unwrap(), fingerprint_zip);
+        assert_ne!(fingerprint("test-aes.zip").unwrap(), fingerprint_zip);
+    }
+
+    #[test]
+    fn should_validate_zip() {
+        let pw_list = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources/pw-list.txt");
+        let missing = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources/missing.zip");
 
-        // Remove the extracted test file.
-        fs::remove_file(extract_path.to_owned() + "test").unwrap();
+        assert!(ZipAdaptor::build(pw_list, "out").is_err());
+        assert!(ZipAdaptor::build(missing, "out").is_err());
     }
 }
@@ -1,4 +1,5 @@
 //! Module for adaptors.
 pub mod attempt_result;
 pub mod base;
+pub mod byte_adaptor;
 pub mod custom;
@@ -0,0 +1,863 @@
+//! Command-line arguments of the `ramensky` binary, and how they map to [`Options`].
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use ramensky::adaptor::custom::hash_adaptor::{DigestFormat, HashAlgorithm, SaltPosition};
+use ramensky::error::RamenskyError;
+use ramensky::safe_cracker::brute_force_options::BruteForceOptions;
+use ramensky::safe_cracker::common_pw_list_options::CommonPwListOptions;
+use ramensky::safe_cracker::encoding_policy::EncodingPolicy;
+use ramensky::safe_cracker::error_policy::ErrorPolicy;
+use ramensky::safe_cracker::options::{Options, OptionsBuilder};
+use ramensky::safe_cracker::reader_configuration::PwListConfiguration;
+
+/// Ramensky is a customisable password cracker.
+///
+/// Exit codes: 0 if the password was found, 1 if it was not found, 2 on errors,
+/// and 3 if the run was interrupted by --max-duration or --max-attempts.
+#[derive(Parser, Debug)]
+#[command(name = "ramensky", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub target: Target,
+
+    /// Configuration file to read the options from, as TOML or JSON. The other flags override the
+    /// values of the file, and password lists are added to the ones of the file.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+
+    #[command(flatten)]
+    pub common_passwords: CommonPwListArgs,
+
+    #[command(flatten)]
+    pub pw_lists: PwListArgs,
+
+    #[command(flatten)]
+    pub brute_force: BruteForceArgs,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+/// What to crack, with one of the built-in adaptors.
+#[derive(Subcommand, Debug)]
+pub enum Target {
+    /// Crack a password protected zip archive, and extract it once the password is found.
+    Zip {
+        /// The encrypted zip archive.
+        archive: String,
+
+        /// Directory to extract the archive to. Created if it does not exist.
+        #[arg(short, long, default_value = ".")]
+        extract_to: String,
+    },
+
+    /// Run a command for every password, the password is correct if it exits with the success code.
+    /// The password is passed in place of every `{}` in the arguments, or written to the standard
+    /// input of the command if none of the arguments contain `{}`.
+    Command {
+        /// Exit code of the command when the password is correct.
+        #[arg(long, default_value_t = 0)]
+        success_code: i32,
+
+        /// The command, and its arguments, e.g. `-- 7z t -p{} archive.7z`.
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+
+    /// Crack a raw password hash, e.g. `5f4dcc3b5aa765d61d8327deb882cf99` for MD5.
+    Hash {
+        /// The digest to crack.
+        digest: String,
+
+        /// Hash algorithm of the digest.
+        #[arg(short, long, value_enum)]
+        algorithm: Algorithm,
+
+        /// How the digest is written.
+        #[arg(long, value_enum, default_value_t = DigestEncoding::Hex)]
+        encoding: DigestEncoding,
+
+        /// Salt that is added to the password before it is hashed.
+        #[arg(long)]
+        salt: Option<String>,
+
+        /// Whether the salt comes before or after the password.
+        #[arg(long, value_enum, default_value_t = SaltAt::After, requires = "salt")]
+        salt_position: SaltAt,
+    },
+}
+
+/// Hash algorithms of the `hash` target.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    /// MD4 of the password encoded as UTF-16LE, used by Windows.
+    Ntlm,
+}
+
+impl From<Algorithm> for HashAlgorithm {
+    fn from(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::Md5 => HashAlgorithm::Md5,
+            Algorithm::Sha1 => HashAlgorithm::Sha1,
+            Algorithm::Sha256 => HashAlgorithm::Sha256,
+            Algorithm::Sha512 => HashAlgorithm::Sha512,
+            Algorithm::Ntlm => HashAlgorithm::Ntlm,
+        }
+    }
+}
+
+/// How the digest of the `hash` target is written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum DigestEncoding {
+    Hex,
+    Base64,
+}
+
+impl From<DigestEncoding> for DigestFormat {
+    fn from(encoding: DigestEncoding) -> Self {
+        match encoding {
+            DigestEncoding::Hex => DigestFormat::Hex,
+            DigestEncoding::Base64 => DigestFormat::Base64,
+        }
+    }
+}
+
+/// Where the salt of the `hash` target is added to the password.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SaltAt {
+    Before,
+    After,
+}
+
+impl From<SaltAt> for SaltPosition {
+    fn from(position: SaltAt) -> Self {
+        match position {
+            SaltAt::Before => SaltPosition::Before,
+            SaltAt::After => SaltPosition::After,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct OutputArgs {
+    /// Only print the password, if it is found.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print every password that is tried.
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct CommonPwListArgs {
+    /// Do not try the common passwords.
+    #[arg(long, global = true)]
+    pub no_common_passwords: bool,
+
+    /// How many of the common passwords to try [default: 1000000].
+    #[arg(
+        long,
+        global = true,
+        value_name = "AMOUNT",
+        conflicts_with = "all_common_passwords"
+    )]
+    pub common_amount: Option<usize>,
+
+    /// Try the whole common password list.
+    #[arg(long, global = true)]
+    pub all_common_passwords: bool,
+
+    /// Common password list to use instead of the embedded one.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub common_list: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct PwListArgs {
+    /// Custom password list, tried after the common passwords. Can be followed by comma separated
+    /// settings, e.g. `words.txt,amount=1000,skip=10,priority=1,encoding=latin1`.
+    /// The encodings are error, skip, lossy, latin1, windows1252 and raw. Can be used multiple times.
+    #[arg(long = "list", global = true, value_name = "PATH[,KEY=VALUE...]")]
+    pub lists: Vec<String>,
+
+    /// Rule file, to apply to every password from the password lists.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub rules: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct BruteForceArgs {
+    /// Do not brute force the password.
+    #[arg(long, global = true)]
+    pub no_brute_force: bool,
+
+    /// Minimum size of the brute forced passwords [default: 2].
+    #[arg(long, global = true)]
+    pub min_size: Option<usize>,
+
+    /// Maximum size of the brute forced passwords [default: 10].
+    #[arg(long, global = true)]
+    pub max_size: Option<usize>,
+
+    /// Do not include numbers when brute forcing.
+    #[arg(long, global = true)]
+    pub no_numbers: bool,
+
+    /// Do not include letters when brute forcing.
+    #[arg(long, global = true)]
+    pub no_characters: bool,
+
+    /// Include symbols when brute forcing.
+    #[arg(long, global = true)]
+    pub symbols: bool,
+
+    /// Characters to brute force with, in order, instead of numbers, letters and symbols.
+    #[arg(long, global = true, value_name = "CHARS", conflicts_with = "mask")]
+    pub charset: Option<String>,
+
+    /// Only brute force passwords matching the mask, e.g. `?u?l?l?l?d?d`.
+    #[arg(long, global = true)]
+    pub mask: Option<String>,
+
+    /// Custom charset of the mask, referenced as `?1` to `?4` in the order they are given.
+    #[arg(long, global = true, value_name = "CHARS", requires = "mask")]
+    pub mask_charset: Vec<String>,
+
+    /// Charset for a position of the password, starting at 0, e.g. `0=ABC`. Can be used multiple times.
+    #[arg(long, global = true, value_name = "POSITION=CHARS", value_parser = parse_position_charset)]
+    pub position_charset: Vec<(usize, String)>,
+}
+
+/// What to do when the adaptor returns an error.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OnError {
+    /// Stop the run.
+    Abort,
+    /// Move on to the next password.
+    Skip,
+    /// Try the password again, waiting longer before every retry.
+    Retry,
+    /// Try the password again later, after the passwords that are already being tried.
+    Requeue,
+}
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// Amount of worker threads that try passwords in parallel [default: 1].
+    #[arg(long, global = true)]
+    pub threads: Option<usize>,
+
+    /// Amount of passwords that every worker hands to the adaptor at once [default: 1].
+    #[arg(long, global = true)]
+    pub batch_size: Option<usize>,
+
+    /// Save a checkpoint to this path periodically, to resume from with --resume.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Seconds between checkpoints [default: 60].
+    #[arg(long, global = true, value_name = "SECONDS", value_parser = parse_duration)]
+    pub checkpoint_interval: Option<Duration>,
+
+    /// Continue from the checkpoint, if it exists. Needs a checkpoint path, from --checkpoint or the
+    /// configuration file.
+    #[arg(long, global = true)]
+    pub resume: bool,
+
+    /// Seconds between progress messages [default: 1].
+    #[arg(long, global = true, value_name = "SECONDS", value_parser = parse_duration)]
+    pub progress_interval: Option<Duration>,
+
+    /// Interrupt the run after this many seconds.
+    #[arg(long, global = true, value_name = "SECONDS", value_parser = parse_duration)]
+    pub max_duration: Option<Duration>,
+
+    /// Interrupt the run after this many passwords were tried.
+    #[arg(long, global = true, value_name = "AMOUNT")]
+    pub max_attempts: Option<u64>,
+
+    /// What to do when the adaptor returns an error [default: abort].
+    #[arg(long, global = true, value_enum)]
+    pub on_error: Option<OnError>,
+
+    /// How many times a password is tried again, with `--on-error retry` or `requeue`.
+    #[arg(long, global = true, default_value_t = 3)]
+    pub retries: u32,
+
+    /// Seconds to wait before trying a password again, with `--on-error retry` or `requeue`.
+    #[arg(long, global = true, value_name = "SECONDS", default_value = "1", value_parser = parse_duration)]
+    pub backoff: Duration,
+
+    /// Potfile to save found passwords to, and to look the target up in before cracking it.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub potfile: Option<PathBuf>,
+}
+
+impl Cli {
+    /// Build the [`Options`] for the arguments, starting from the configuration file if provided, with the
+    /// same validation as the library.
+    pub fn options(&self) -> Result<Options, RamenskyError> {
+        // Without a configuration file, only the summary of the binary is printed, unless --verbose is given.
+        let options = match &self.config {
+            Some(path) => Options::load(path)?,
+            None => Options {
+                quiet: true,
+                ..Options::default()
+            },
+        };
+
+        let run = &self.run;
+        let mut builder = OptionsBuilder::from(options.clone());
+
+        if self.output.quiet || self.output.verbose {
+            builder = builder.quiet(!self.output.verbose);
+        }
+
+        if self.common_passwords.no_common_passwords {
+            builder = builder.try_common_passwords(false);
+        }
+
+        let common_amount = match self.common_passwords.all_common_passwords {
+            true => Some(None),
+            false => self.common_passwords.common_amount.map(Some),
+        };
+
+        if let Some(common_amount) = common_amount {
+            builder = builder.common_password_options(CommonPwListOptions::build(common_amount)?);
+        }
+
+        if let Some(path) = &self.common_passwords.common_list {
+            builder = builder.common_pw_list_path(path);
+        }
+
+        if self.brute_force.no_brute_force {
+            builder = builder.try_brute_forcing(false);
+        } else if self.brute_force.is_set() {
+            let base = options.brute_forcing_options.clone().unwrap_or_default();
+            builder = builder.brute_forcing_options(self.brute_force.options(&base)?);
+        }
+
+        for list in &self.pw_lists.lists {
+            builder = builder.custom_pw_list(parse_pw_list(list)?);
+        }
+
+        if let Some(path) = &self.pw_lists.rules {
+            builder = builder.rules_path(path);
+        }
+
+        if let Some(threads) = run.threads {
+            builder = builder.threads(threads);
+        }
+
+        if let Some(batch_size) = run.batch_size {
+            builder = builder.batch_size(batch_size);
+        }
+
+        if let Some(path) = &run.checkpoint {
+            builder = builder.checkpoint_path(path);
+        }
+
+        if let Some(checkpoint_interval) = run.checkpoint_interval {
+            builder = builder.checkpoint_interval(checkpoint_interval);
+        }
+
+        if let Some(progress_interval) = run.progress_interval {
+            builder = builder.progress_interval(progress_interval);
+        }
+
+        if let Some(max_duration) = run.max_duration {
+            builder = builder.max_duration(max_duration);
+        }
+
+        if let Some(max_attempts) = run.max_attempts {
+            builder = builder.max_attempts(max_attempts);
+        }
+
+        if let Some(error_policy) = run.error_policy() {
+            builder = builder.error_policy(error_policy);
+        }
+
+        if let Some(path) = &run.potfile {
+            builder = builder.potfile_path(path);
+        }
+
+        let options = builder.build()?;
+
+        if run.resume && options.checkpoint_path.is_none() {
+            return Err(RamenskyError::InvalidConfiguration(
+                "--resume needs a checkpoint path, from --checkpoint or the configuration file."
+                    .to_string(),
+            ));
+        }
+
+        Ok(options)
+    }
+}
+
+impl RunArgs {
+    fn error_policy(&self) -> Option<ErrorPolicy> {
+        let error_policy = match self.on_error? {
+            OnError::Abort => ErrorPolicy::Abort,
+            OnError::Skip => ErrorPolicy::Skip,
+            OnError::Retry => ErrorPolicy::Retry {
+                retries: self.retries,
+                backoff: self.backoff,
+            },
+            OnError::Requeue => ErrorPolicy::Requeue {
+                pause: self.backoff,
+                max_requeues: self.retries,
+            },
+        };
+
+        Some(error_policy)
+    }
+}
+
+impl BruteForceArgs {
+    /// Whether any of the brute forcing flags were provided, so they override the brute forcing options
+    /// of the configuration file.
+    fn is_set(&self) -> bool {
+        self.changes_size()
+            || self.charset.is_some()
+            || self.mask.is_some()
+            || !self.position_charset.is_empty()
+    }
+
+    /// Whether any of the flags that do not apply to masks were provided.
+    fn changes_size(&self) -> bool {
+        self.min_size.is_some()
+            || self.max_size.is_some()
+            || self.no_numbers
+            || self.no_characters
+            || self.symbols
+    }
+
+    /// Build the brute forcing options from `base`, the options of the configuration file or the defaults,
+    /// with the values of the provided flags.
+    fn options(&self, base: &BruteForceOptions) -> Result<BruteForceOptions, RamenskyError> {
+        let min_size = self.min_size.unwrap_or(base.minimum_size);
+        let max_size = self.max_size.unwrap_or(base.maximum_size);
+        let base_charset: Option<String> = base.custom_charset.as_ref().map(|c| c.iter().collect());
+
+        let options = match (&self.mask, &self.charset, &base.mask, &base_charset) {
+            (Some(mask), ..) => {
+                let charsets: Vec<&str> = self.mask_charset.iter().map(String::as_str).collect();
+                BruteForceOptions::build_mask(mask, &charsets)?
+            }
+            (None, Some(charset), ..) => {
+                BruteForceOptions::build_custom(min_size, max_size, charset)?
+            }
+            (None, None, Some(_), _) if self.changes_size() => {
+                return Err(RamenskyError::InvalidConfiguration(
+                    "The mask of the configuration file cannot be used with --min-size, --max-size, \
+                    --no-numbers, --no-characters or --symbols."
+                        .to_string(),
+                ));
+            }
+            (None, None, Some(_), _) => base.clone(),
+            (None, None, None, Some(charset)) => {
+                BruteForceOptions::build_custom(min_size, max_size, charset)?
+            }
+            (None, None, None, None) => BruteForceOptions::build(
+                min_size,
+                max_size,
+                base.include_numbers && !self.no_numbers,
+                base.include_characters && !self.no_characters,
+                base.include_symbols || self.symbols,
+            )?,
+        };
+
+        if self.position_charset.is_empty() {
+            if options.mask.is_some() || base.position_charsets.is_empty() {
+                return Ok(options);
+            }
+
+            let position_charsets: Vec<Option<String>> = base
+                .position_charsets
+                .iter()
+                .map(|c| c.as_ref().map(|c| c.iter().collect()))
+                .collect();
+            let position_charsets: Vec<Option<&str>> =
+                position_charsets.iter().map(Option::as_deref).collect();
+
+            return options.with_position_charsets(&position_charsets);
+        }
+
+        let size = self
+            .position_charset
+            .iter()
+            .map(|(position, _)| position + 1)
+            .max()
+            .unwrap_or(0);
+        let mut position_charsets = vec![None; size];
+
+        for (position, charset) in &self.position_charset {
+            position_charsets[*position] = Some(charset.as_str());
+        }
+
+        options.with_position_charsets(&position_charsets)
+    }
+}
+
+/// Parse a password list, followed by its comma separated settings. Settings are read from the end,
+/// so paths that contain commas can still be used.
+fn parse_pw_list(list: &str) -> Result<PwListConfiguration, RamenskyError> {
+    let mut path = list;
+    let mut settings = vec![];
+
+    while let Some((rest, setting)) = path.rsplit_once(',') {
+        match setting.split_once('=') {
+            Some((key, value)) if ["amount", "skip", "priority", "encoding"].contains(&key) => {
+                settings.push((key, value));
+                path = rest;
+            }
+            _ => break,
+        }
+    }
+
+    let invalid = |key: &str, value: &str| {
+        RamenskyError::InvalidConfiguration(format!(
+            "Invalid {key} ({value}) for password list {path}."
+        ))
+    };
+
+    let mut configuration = PwListConfiguration::new(path, None);
+
+    for (key, value) in settings {
+        match key {
+            "amount" => {
+                configuration.amount = Some(value.parse().map_err(|_| invalid(key, value))?)
+            }
+            "skip" => configuration.skip = value.parse().map_err(|_| invalid(key, value))?,
+            "priority" => {
+                configuration.priority = value.parse().map_err(|_| invalid(key, value))?
+            }
+            _ => {
+                configuration.encoding = match value {
+                    "error" => EncodingPolicy::Error,
+                    "skip" => EncodingPolicy::Skip,
+                    "lossy" => EncodingPolicy::Lossy,
+                    "latin1" => EncodingPolicy::Latin1,
+                    "windows1252" => EncodingPolicy::Windows1252,
+                    "raw" => EncodingPolicy::Raw,
+                    _ => return Err(invalid(key, value)),
+                }
+            }
+        }
+    }
+
+    Ok(configuration)
+}
+
+fn parse_position_charset(value: &str) -> Result<(usize, String), String> {
+    let (position, charset) = value
+        .split_once('=')
+        .ok_or_else(|| format!("{value} needs to be POSITION=CHARS, e.g. 0=ABC."))?;
+    let position = position
+        .parse()
+        .map_err(|_| format!("{position} is not a valid position."))?;
+
+    Ok((position, charset.to_string()))
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    value
+        .parse()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("{value} is not a valid amount of seconds."))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use clap::{CommandFactory, Parser};
+
+    use ramensky::error::RamenskyError;
+    use ramensky::safe_cracker::encoding_policy::EncodingPolicy;
+    use ramensky::safe_cracker::error_policy::ErrorPolicy;
+    use ramensky::safe_cracker::options::Options;
+
+    use crate::args::{parse_pw_list, Algorithm, Cli, DigestEncoding, SaltAt, Target};
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from([&["ramensky"], args].concat()).unwrap()
+    }
+
+    #[test]
+    fn can_verify_arguments() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn can_parse_defaults() {
+        let cli = parse(&["zip", "test.zip"]);
+        let options = cli.options().unwrap();
+
+        assert!(matches!(
+            cli.target,
+            Target::Zip { ref archive, ref extract_to } if archive == "test.zip" && extract_to == "."
+        ));
+        assert!(options.quiet);
+        assert!(options.try_common_passwords);
+        assert_eq!(
+            options.common_password_options.unwrap().amount_to_use,
+            Some(1_000_000)
+        );
+        assert!(options.try_brute_forcing);
+        assert_eq!(options.brute_forcing_options.unwrap().charset().len(), 62);
+        assert!(options.custom_pw_lists.is_empty());
+        assert_eq!(options.threads, 1);
+        assert_eq!(options.error_policy, ErrorPolicy::Abort);
+    }
+
+    #[test]
+    fn can_parse_options() {
+        let cli = parse(&[
+            "command",
+            "--success-code",
+            "3",
+            "--threads",
+            "4",
+            "--all-common-passwords",
+            "--list",
+            "tests/resources/pw-list.txt,amount=10,encoding=latin1",
+            "--mask",
+            "?u?1",
+            "--mask-charset",
+            "!#",
+            "--on-error",
+            "retry",
+            "--backoff",
+            "0.5",
+            "--potfile",
+            "ramensky.pot",
+            "-v",
+            "--",
+            "7z",
+            "t",
+            "-p{}",
+        ]);
+        let options = cli.options().unwrap();
+
+        assert!(matches!(
+            cli.target,
+            Target::Command { success_code: 3, ref command } if command == &["7z", "t", "-p{}"]
+        ));
+        assert!(!options.quiet);
+        assert_eq!(options.threads, 4);
+        assert_eq!(options.common_password_options.unwrap().amount_to_use, None);
+        assert_eq!(options.custom_pw_lists[0].amount, Some(10));
+        assert_eq!(
+            options.brute_forcing_options.unwrap().keyspace_size(),
+            Some(26 * 2)
+        );
+        assert_eq!(
+            options.error_policy,
+            ErrorPolicy::Retry {
+                retries: 3,
+                backoff: Duration::from_millis(500)
+            }
+        );
+        assert_eq!(options.potfile_path, Some(PathBuf::from("ramensky.pot")));
+    }
+
+    #[test]
+    fn can_parse_hashes() {
+        let cli = parse(&[
+            "hash",
+            "W6ph5Mm5Pz8GgiULbPgzG37mj9g=",
+            "-a",
+            "sha1",
+            "--encoding",
+            "base64",
+            "--salt",
+            "pepper",
+            "--salt-position",
+            "before",
+        ]);
+
+        assert!(matches!(
+            cli.target,
+            Target::Hash {
+                algorithm: Algorithm::Sha1,
+                encoding: DigestEncoding::Base64,
+                salt: Some(ref salt),
+                salt_position: SaltAt::Before,
+                ..
+            } if salt == "pepper"
+        ));
+
+        let cli = parse(&["hash", "5f4dcc3b5aa765d61d8327deb882cf99", "-a", "md5"]);
+
+        assert!(matches!(
+            cli.target,
+            Target::Hash {
+                encoding: DigestEncoding::Hex,
+                salt: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn can_parse_position_charsets() {
+        let cli = parse(&[
+            "zip",
+            "test.zip",
+            "--max-size",
+            "3",
+            "--position-charset",
+            "2=ab",
+            "--position-charset",
+            "0=X",
+        ]);
+        let charsets = cli
+            .options()
+            .unwrap()
+            .brute_forcing_options
+            .unwrap()
+            .charsets();
+
+        assert_eq!(charsets[0], ['X']);
+        assert_eq!(charsets[1].len(), 62);
+        assert_eq!(charsets[2], ['a', 'b']);
+    }
+
+    #[test]
+    fn can_parse_pw_lists() {
+        let list = parse_pw_list("a,b.txt,skip=5,priority=-1,encoding=raw").unwrap();
+
+        assert_eq!(list.source.to_string(), "a,b.txt");
+        assert_eq!(list.skip, 5);
+        assert_eq!(list.priority, -1);
+        assert_eq!(list.encoding, EncodingPolicy::Raw);
+        assert_eq!(list.amount, None);
+
+        assert!(parse_pw_list("words.txt,amount=many").is_err());
+        assert!(parse_pw_list("words.txt,encoding=utf16").is_err());
+    }
+
+    /// Build the options with a configuration file of the given contents, followed by `args`.
+    fn options_with_config(
+        name: &str,
+        config: &str,
+        args: &[&str],
+    ) -> Result<Options, RamenskyError> {
+        let path = env::temp_dir().join(format!("ramensky-cli-{name}-test.toml"));
+        fs::write(&path, config).unwrap();
+
+        let mut all_args = vec!["zip", "test.zip", "--config", path.to_str().unwrap()];
+        all_args.extend_from_slice(args);
+        let options = parse(&all_args).options();
+        fs::remove_file(&path).unwrap();
+
+        options
+    }
+
+    #[test]
+    fn can_override_config_file() {
+        let config = "quiet = false\nthreads = 8\nmax_attempts = 5\ntry_brute_forcing = false\n\
+            checkpoint_path = \"run.checkpoint\"\n";
+
+        let options =
+            options_with_config("override", config, &["--threads", "2", "--resume"]).unwrap();
+
+        assert!(!options.quiet);
+        assert_eq!(options.threads, 2);
+        assert_eq!(options.max_attempts, Some(5));
+        assert!(!options.try_brute_forcing);
+        assert_eq!(
+            options.checkpoint_path,
+            Some(PathBuf::from("run.checkpoint"))
+        );
+        assert_eq!(
+            options.common_password_options.unwrap().amount_to_use,
+            Some(1_000_000)
+        );
+
+        assert!(options_with_config("quiet", config, &["-q"]).unwrap().quiet);
+    }
+
+    #[test]
+    fn can_override_brute_forcing_of_config_file() {
+        let config = "[brute_forcing_options]\nminimum_size = 1\nmaximum_size = 3\n\
+            custom_charset = \"abc\"\nposition_charsets = [\"X\"]\n";
+
+        let options = options_with_config("brute-force", config, &["--max-size", "6"])
+            .unwrap()
+            .brute_forcing_options
+            .unwrap();
+
+        assert_eq!(options.minimum_size, 1);
+        assert_eq!(options.maximum_size, 6);
+        assert_eq!(options.charset(), ['a', 'b', 'c']);
+        assert_eq!(options.charsets()[0], ['X']);
+
+        let config = "[brute_forcing_options]\nmask = \"?u?d\"\n";
+
+        assert!(options_with_config("mask", config, &["--max-size", "6"]).is_err());
+        assert_eq!(
+            options_with_config("mask", config, &["--mask", "?d"])
+                .unwrap()
+                .brute_forcing_options
+                .unwrap()
+                .keyspace_size(),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn should_validate_arguments() {
+        let invalid = |args: &[&str]| {
+            let mut args = args.to_vec();
+            args.insert(0, "ramensky");
+            match Cli::try_parse_from(args) {
+                Ok(cli) => cli.options().is_err(),
+                Err(_) => true,
+            }
+        };
+
+        assert!(invalid(&["zip"]));
+        assert!(invalid(&["command"]));
+        assert!(invalid(&["hash", "5f4dcc3b5aa765d61d8327deb882cf99"]));
+        assert!(invalid(&[
+            "hash",
+            "5f4dcc3b5aa765d61d8327deb882cf99",
+            "-a",
+            "md5",
+            "--salt-position",
+            "before"
+        ]));
+        assert!(invalid(&["zip", "test.zip", "-q", "-v"]));
+        assert!(invalid(&["zip", "test.zip", "--resume"]));
+        assert!(invalid(&["zip", "test.zip", "--config", "missing.toml"]));
+        assert!(invalid(&[
+            "zip",
+            "test.zip",
+            "--min-size",
+            "5",
+            "--max-size",
+            "5"
+        ]));
+        assert!(invalid(&["zip", "test.zip", "--common-amount", "20000000"]));
+        assert!(invalid(&["zip", "test.zip", "--progress-interval", "-1"]));
+        assert!(invalid(&["zip", "test.zip", "--position-charset", "ABC"]));
+    }
+}
@@ -0,0 +1,188 @@
+//! The `ramensky` binary, that cracks passwords with the built-in adaptors from the command line.
+//! Run `ramensky --help` to see every option.
+
+mod args;
+
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use ramensky::adaptor::byte_adaptor::ByteAdaptor;
+use ramensky::adaptor::custom::command_adaptor::CommandAdaptor;
+use ramensky::adaptor::custom::hash_adaptor::HashAdaptor;
+use ramensky::adaptor::custom::zip_adaptor::ZipAdaptor;
+use ramensky::error::RamenskyError;
+use ramensky::safe_cracker::checkpoint::Checkpoint;
+use ramensky::safe_cracker::observer::{Event, Observer, PrintObserver};
+use ramensky::safe_cracker::password_crack_result::PasswordCrackResult;
+use ramensky::safe_cracker::safe_cracker::SafeCracker;
+use ramensky::safe_cracker::statistics::PasswordSource;
+
+use crate::args::{Cli, Target};
+
+/// The password was found.
+const FOUND: u8 = 0;
+
+/// Every password was tried, without finding the password.
+const NOT_FOUND: u8 = 1;
+
+/// The arguments are not valid, or the run failed. Also used by clap for invalid arguments.
+const ERROR: u8 = 2;
+
+/// The run was stopped before every password was tried.
+const INTERRUPTED: u8 = 3;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(&cli) {
+        Ok(result) => ExitCode::from(exit_code(&result)),
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::from(ERROR)
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<PasswordCrackResult, RamenskyError> {
+    match &cli.target {
+        Target::Zip {
+            archive,
+            extract_to,
+        } => {
+            let result = crack(cli, ZipAdaptor::build(archive, extract_to)?)?;
+
+            // Passwords from the potfile are not tried, so the archive has not been extracted yet.
+            if let PasswordCrackResult::Success(password, statistics) = &result {
+                if let Some(PasswordSource::Potfile { .. }) = statistics.found_by {
+                    ZipAdaptor::build(archive, extract_to)?.extract(password.as_bytes())?;
+                }
+            }
+
+            Ok(result)
+        }
+        Target::Command {
+            success_code,
+            command,
+        } => {
+            let args: Vec<&str> = command[1..].iter().map(String::as_str).collect();
+            crack(
+                cli,
+                CommandAdaptor::build(&command[0], &args, *success_code)?,
+            )
+        }
+        Target::Hash {
+            digest,
+            algorithm,
+            encoding,
+            salt,
+            salt_position,
+        } => {
+            let mut adaptor = HashAdaptor::build((*algorithm).into(), digest, (*encoding).into())?;
+
+            if let Some(salt) = salt {
+                adaptor = adaptor.with_salt(salt.as_str(), (*salt_position).into());
+            }
+
+            crack(cli, adaptor)
+        }
+    }
+}
+
+fn crack<T: ByteAdaptor + Sync>(
+    cli: &Cli,
+    adaptor: T,
+) -> Result<PasswordCrackResult, RamenskyError> {
+    let options = cli.options()?;
+    // Runs that are not quiet print every event with the `PrintObserver` of the options instead.
+    let print_summary = options.quiet && !cli.output.quiet;
+
+    let checkpoint = match &options.checkpoint_path {
+        Some(path) if cli.run.resume && path.exists() => Some(Checkpoint::load(path)?),
+        _ => None,
+    };
+
+    let mut safe_cracker = match checkpoint {
+        Some(checkpoint) => SafeCracker::resume(options, &checkpoint)?,
+        None => SafeCracker::build(options)?,
+    };
+
+    if print_summary {
+        safe_cracker = safe_cracker.with_observer(|event: &Event| {
+            if !matches!(event, Event::Attempt(_)) {
+                PrintObserver.on_event(event);
+            }
+        });
+    }
+
+    let result = safe_cracker.start(adaptor)?;
+
+    if let (true, PasswordCrackResult::Success(password, _)) = (cli.output.quiet, &result) {
+        println!("{password}");
+    }
+
+    Ok(result)
+}
+
+fn exit_code(result: &PasswordCrackResult) -> u8 {
+    match result {
+        PasswordCrackResult::Success(..) => FOUND,
+        PasswordCrackResult::Failure(_) => NOT_FOUND,
+        PasswordCrackResult::Interrupted(..) => INTERRUPTED,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use clap::Parser;
+
+    use ramensky::safe_cracker::password_crack_result::PasswordCrackResult;
+    use ramensky::safe_cracker::statistics::PasswordSource;
+
+    use crate::args::Cli;
+    use crate::run;
+
+    #[test]
+    fn should_extract_zip_found_in_potfile() {
+        let potfile = env::temp_dir().join("ramensky-cli-potfile-test.txt");
+        let extract_to = env::temp_dir().join("ramensky-cli-potfile-test");
+        let _ = fs::remove_file(&potfile);
+
+        let cli = Cli::try_parse_from([
+            "ramensky",
+            "zip",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources/test.zip"),
+            "--extract-to",
+            extract_to.to_str().unwrap(),
+            "--potfile",
+            potfile.to_str().unwrap(),
+            "--no-common-passwords",
+            "--mask",
+            "tes?l",
+            "-q",
+        ])
+        .unwrap();
+
+        // The first run tries the passwords, the second one finds the password in the potfile.
+        for from_potfile in [false, true] {
+            let _ = fs::remove_dir_all(&extract_to);
+
+            let result = run(&cli).unwrap();
+            let contents = fs::read_to_string(extract_to.join("test")).ok();
+
+            assert!(matches!(
+                &result,
+                PasswordCrackResult::Success(password, statistics)
+                    if password.to_str() == Some("test")
+                        && matches!(statistics.found_by, Some(PasswordSource::Potfile { .. })) == from_potfile
+            ));
+            assert_eq!(contents.as_deref(), Some("test\n"));
+        }
+
+        fs::remove_file(&potfile).unwrap();
+        fs::remove_dir_all(&extract_to).unwrap();
+    }
+}
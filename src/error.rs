@@ -0,0 +1,67 @@
+//! Module containing the error type, that is returned by every fallible function of Ramensky.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::Path;
+
+/// Errors returned by Ramensky.
+#[derive(Debug)]
+pub enum RamenskyError {
+    /// The options, a mask, a rule or a checkpoint are not valid. Contains the reason.
+    InvalidConfiguration(String),
+
+    /// A file could not be read or written, e.g. a password list that does not exist.
+    Io { path: String, source: io::Error },
+
+    /// A password list contains a line that is not valid UTF-8.
+    Encoding { path: String, line: usize },
+
+    /// The adaptor failed to try a password, e.g. because the target could not be reached.
+    Adaptor(Box<dyn Error + Send + Sync>),
+
+    /// The run was cancelled. Adaptors can return this from
+    /// [`try_password()`](crate::adaptor::base::BaseAdaptor::try_password) to stop the run, which is
+    /// then interrupted instead of failing.
+    Cancelled,
+}
+
+impl RamenskyError {
+    /// Wrap an error of an adaptor, e.g. `.map_err(RamenskyError::adaptor)?`.
+    /// Strings can be used as well, e.g. `RamenskyError::adaptor("Connection refused.")`.
+    pub fn adaptor(error: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        RamenskyError::Adaptor(error.into())
+    }
+
+    /// Wrap an I/O error of the file at `path`.
+    pub fn io(path: impl AsRef<Path>, source: io::Error) -> Self {
+        RamenskyError::Io {
+            path: path.as_ref().display().to_string(),
+            source,
+        }
+    }
+}
+
+impl Display for RamenskyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RamenskyError::InvalidConfiguration(reason) => write!(f, "{reason}"),
+            RamenskyError::Io { path, source } => write!(f, "Could not access {path}: {source}"),
+            RamenskyError::Encoding { path, line } => {
+                write!(f, "Line {line} of {path} is not valid UTF-8.")
+            }
+            RamenskyError::Adaptor(e) => write!(f, "Adaptor error: {e}"),
+            RamenskyError::Cancelled => write!(f, "The run was cancelled."),
+        }
+    }
+}
+
+impl Error for RamenskyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RamenskyError::Io { source, .. } => Some(source),
+            RamenskyError::Adaptor(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! Module containing the hexadecimal encoding of bytes, used by potfiles, fingerprints and digests.
+
+/// Encode the bytes as lowercase hexadecimal.
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Decode hexadecimal, in lower or upper case. Returns [`None`] if `hex` contains anything else, or has
+/// an odd length.
+pub(crate) fn decode(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hex::{decode, encode};
+
+    #[test]
+    fn can_encode_and_decode() {
+        assert_eq!(encode(b"caf\xe9"), "636166e9");
+        assert_eq!(decode("636166E9").unwrap(), b"caf\xe9");
+        assert_eq!(decode("").unwrap(), b"");
+    }
+
+    #[test]
+    fn should_validate_hex() {
+        for hex in ["+f", "-1", "0", "0g", " 00", "é0"] {
+            assert_eq!(decode(hex), None, "{hex}");
+        }
+    }
+}
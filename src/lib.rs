@@ -3,4 +3,6 @@
 //! Ramensky is a customisable password cracker.
 
 pub mod adaptor;
+pub mod error;
+mod hex;
 pub mod safe_cracker;
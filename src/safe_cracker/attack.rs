@@ -0,0 +1,250 @@
+//! Module containing the attacks, that make up the stages of the pipeline of `SafeCracker`.
+//!
+//! Stages are run in the order they are provided, and every stage can have its own budget. For example,
+//! to try the 10,000 most common passwords, then a company specific list with rules, then 6 character
+//! masks for an hour at most, and then the full common password list:
+//! ```rust,no_run
+//! use std::time::Duration;
+//!
+//! use ramensky::safe_cracker::attack::{Attack, AttackStage};
+//! use ramensky::safe_cracker::options::Options;
+//! use ramensky::safe_cracker::reader_configuration::PwListConfiguration;
+//!
+//! let options = Options::builder()
+//!     .stage(AttackStage::new(Attack::Wordlist {
+//!         list: PwListConfiguration::embedded_common_passwords(Some(10_000)),
+//!     }))
+//!     .stage(AttackStage::new(Attack::Rules {
+//!         list: PwListConfiguration::new("company.txt", None),
+//!         rules_path: "rules.txt".into(),
+//!     }))
+//!     .stage(
+//!         AttackStage::new(Attack::Mask {
+//!             mask: "?a?a?a?a?a?a".to_string(),
+//!             mask_charsets: vec![],
+//!         })
+//!         .with_max_duration(Duration::from_secs(60 * 60)),
+//!     )
+//!     .stage(AttackStage::new(Attack::Wordlist {
+//!         list: PwListConfiguration::embedded_common_passwords(None),
+//!     }))
+//!     .build()
+//!     .unwrap();
+//! ```
+
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::RamenskyError;
+use crate::safe_cracker::brute_force_options::BruteForceOptions;
+use crate::safe_cracker::reader_configuration::PwListConfiguration;
+use crate::safe_cracker::rule::Rule;
+
+/// How the passwords of a stage are generated.
+///
+/// In configuration files, the kind of attack is the `attack` key of the stage, e.g. `attack = "mask"`.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "attack", rename_all = "snake_case", deny_unknown_fields)
+)]
+pub enum Attack {
+    /// Every password of a password list.
+    Wordlist { list: PwListConfiguration },
+
+    /// Every rule of the rule file at `rules_path` applied to every password of a password list,
+    /// see [`Rule`] for the syntax.
+    Rules {
+        list: PwListConfiguration,
+        rules_path: PathBuf,
+    },
+
+    /// Every password matching the mask, see [`Mask`](crate::safe_cracker::mask::Mask) for the syntax.
+    /// `mask_charsets` are the charsets referenced in the mask as `?1`..`?4`.
+    Mask {
+        mask: String,
+        #[cfg_attr(feature = "config", serde(default))]
+        mask_charsets: Vec<String>,
+    },
+
+    /// Every password of the brute forcing keyspace.
+    BruteForce { options: BruteForceOptions },
+
+    /// Every password of the `left` list, followed by every password of the `right` list, e.g. `summer`
+    /// and `2024` for `summer2024`. The `right` list is kept in memory.
+    Combinator {
+        left: PwListConfiguration,
+        right: PwListConfiguration,
+    },
+
+    /// Every password of a password list, followed by every password matching the mask, e.g. `summer`
+    /// and `?d?d?d?d` for `summer2024`. If `mask_first` is true, the mask comes before the password instead.
+    Hybrid {
+        list: PwListConfiguration,
+        mask: String,
+        #[cfg_attr(feature = "config", serde(default))]
+        mask_charsets: Vec<String>,
+        #[cfg_attr(feature = "config", serde(default))]
+        mask_first: bool,
+    },
+}
+
+impl Attack {
+    /// Check that the password lists and rule files exist, and that the masks are valid.
+    pub fn validate(&self) -> Result<(), RamenskyError> {
+        match self {
+            Attack::Wordlist { list } => list.validate(),
+            Attack::Rules { list, rules_path } => {
+                list.validate()?;
+                Rule::load(rules_path).map(drop)
+            }
+            Attack::Mask {
+                mask,
+                mask_charsets,
+            } => mask_options(mask, mask_charsets).map(drop),
+            Attack::BruteForce { .. } => Ok(()),
+            Attack::Combinator { left, right } => {
+                left.validate()?;
+                right.validate()
+            }
+            Attack::Hybrid {
+                list,
+                mask,
+                mask_charsets,
+                ..
+            } => {
+                list.validate()?;
+                mask_options(mask, mask_charsets).map(drop)
+            }
+        }
+    }
+}
+
+impl Display for Attack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Attack::Wordlist { .. } => "wordlist",
+            Attack::Rules { .. } => "rules",
+            Attack::Mask { .. } => "mask",
+            Attack::BruteForce { .. } => "brute_force",
+            Attack::Combinator { .. } => "combinator",
+            Attack::Hybrid { .. } => "hybrid",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// Build the brute forcing options of a mask, and its charsets.
+pub(crate) fn mask_options(
+    mask: &str,
+    mask_charsets: &[String],
+) -> Result<BruteForceOptions, RamenskyError> {
+    let charsets: Vec<&str> = mask_charsets.iter().map(String::as_str).collect();
+    BruteForceOptions::build_mask(mask, &charsets)
+}
+
+/// A stage of the pipeline: an attack, and the budget it can use before the next stage starts.
+///
+/// In configuration files, the attack and its budget are part of the same table:
+/// ```toml
+/// [[pipeline]]
+/// attack = "mask"
+/// mask = "?u?l?l?l?d?d"
+/// max_attempts = 1000000
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttackStage {
+    #[cfg_attr(feature = "config", serde(flatten))]
+    pub attack: Attack,
+
+    /// If provided, the stage stops once it has generated this many passwords.
+    #[cfg_attr(
+        feature = "config",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub max_attempts: Option<u64>,
+
+    /// If provided, the stage stops once it has been running for this long. The time is counted from the
+    /// first password of the stage, and starts over if the run is resumed from a checkpoint.
+    #[cfg_attr(
+        feature = "config",
+        serde(
+            default,
+            with = "crate::safe_cracker::config::optional_seconds",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub max_duration: Option<Duration>,
+}
+
+impl AttackStage {
+    /// A stage that runs the attack until every password is generated.
+    pub fn new(attack: Attack) -> Self {
+        AttackStage {
+            attack,
+            max_attempts: None,
+            max_duration: None,
+        }
+    }
+
+    /// Stop the stage once it has generated `max_attempts` passwords.
+    pub fn with_max_attempts(mut self, max_attempts: u64) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Stop the stage once it has been running for `max_duration`.
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::safe_cracker::attack::Attack;
+    use crate::safe_cracker::reader_configuration::PwListConfiguration;
+
+    #[test]
+    fn should_validate() {
+        let list = PwListConfiguration::new("tests/resources/pw-list.txt", None);
+        let missing = PwListConfiguration::new("tests/resources/missing.txt", None);
+
+        assert!(Attack::Wordlist { list: list.clone() }.validate().is_ok());
+        assert!(Attack::Wordlist {
+            list: missing.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(Attack::Rules {
+            list: list.clone(),
+            rules_path: "tests/resources/missing-rules.txt".into(),
+        }
+        .validate()
+        .is_err());
+        assert!(Attack::Combinator {
+            left: list.clone(),
+            right: missing,
+        }
+        .validate()
+        .is_err());
+        assert!(Attack::Mask {
+            mask: "?d?1".to_string(),
+            mask_charsets: vec!["ab".to_string()],
+        }
+        .validate()
+        .is_ok());
+        assert!(Attack::Hybrid {
+            list,
+            mask: "?1".to_string(),
+            mask_charsets: vec![],
+            mask_first: false,
+        }
+        .validate()
+        .is_err());
+    }
+}
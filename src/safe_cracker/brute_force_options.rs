@@ -1,8 +1,28 @@
 //! Module containing the options that configure how brute forcing step is done.
 
-use std::error::Error;
+use crate::error::RamenskyError;
+use crate::safe_cracker::mask::Mask;
 
-#[derive(Copy, Clone)]
+/// Characters used when `include_numbers` is true.
+pub const NUMBERS: &str = "0123456789";
+
+/// Lowercase characters used when `include_characters` is true.
+pub const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+
+/// Uppercase characters used when `include_characters` is true.
+pub const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Characters used when `include_symbols` is true.
+pub const SYMBOLS: &str = " !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// In configuration files, the charsets are strings, and the position charsets that are not provided
+/// are empty strings, e.g. `position_charsets = ["ABC", "", "!?"]`.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "BruteForceOptionsData", try_from = "BruteForceOptionsData")
+)]
 pub struct BruteForceOptions {
     /// Minimum size of the password being searched.
     pub minimum_size: usize,
@@ -18,46 +38,329 @@ pub struct BruteForceOptions {
 
     /// Whether to include symbols in the search.
     pub include_symbols: bool,
+
+    /// If provided, these characters will be searched in the given order, and the `include_*`
+    /// values are ignored. Can contain any Unicode character, like `ä`, `ß` or `ç`.
+    pub custom_charset: Option<Vec<char>>,
+
+    /// Charsets for specific positions of the password, e.g. the first character is always uppercase.
+    /// The charset at index `i` is used for the `i`th character, if it is [`None`] or not provided,
+    /// the charset from `custom_charset` or the `include_*` values is used instead.
+    pub position_charsets: Vec<Option<Vec<char>>>,
+
+    /// If provided, only passwords matching the mask will be searched, and the `include_*`
+    /// values are ignored. See [`Mask`] for the syntax.
+    pub mask: Option<Mask>,
 }
 
-impl BruteForceOptions {
-    pub fn default() -> Self {
+impl Default for BruteForceOptions {
+    fn default() -> Self {
         Self::build(2, 10, true, true, false).unwrap()
     }
+}
 
+impl BruteForceOptions {
     pub fn build(
         minimum_size: usize,
         maximum_size: usize,
         include_numbers: bool,
         include_characters: bool,
         include_symbols: bool,
-    ) -> Result<Self, Box<dyn Error>> {
-        if minimum_size == maximum_size {
-            return Err(format!(
-                "minimum_size and maximum_size cannot be the same ({maximum_size})."
-            ))?;
+    ) -> Result<Self, RamenskyError> {
+        BruteForceOptions {
+            maximum_size,
+            minimum_size,
+            include_characters,
+            include_numbers,
+            include_symbols,
+            custom_charset: None,
+            position_charsets: vec![],
+            mask: None,
         }
+        .validate()
+    }
 
-        if minimum_size >= maximum_size {
-            return Err(format!(
-                "minimum_size ({minimum_size} cannot be bigger than maximum_size ({maximum_size})."
-            ))?;
+    /// Build options that search the characters of `custom_charset`, in the given order.
+    /// Duplicate characters are only searched once.
+    pub fn build_custom(
+        minimum_size: usize,
+        maximum_size: usize,
+        custom_charset: &str,
+    ) -> Result<Self, RamenskyError> {
+        BruteForceOptions {
+            maximum_size,
+            minimum_size,
+            include_characters: false,
+            include_numbers: false,
+            include_symbols: false,
+            custom_charset: Some(unique_chars(custom_charset.chars())),
+            position_charsets: vec![],
+            mask: None,
         }
+        .validate()
+    }
+
+    /// Build options for a mask attack, which only searches passwords of the given shape,
+    /// e.g. `?u?l?l?l?l?l?d?d?d?d?s` for `Summer2024!`.
+    /// `custom_charsets` are the charsets referenced in the mask as `?1`..`?4`.
+    pub fn build_mask(mask: &str, custom_charsets: &[&str]) -> Result<Self, RamenskyError> {
+        let mask = Mask::build(mask, custom_charsets)?;
 
-        if include_numbers == false && include_symbols == false && include_characters == false {
-            return Err(format!(
-                "At least one of the include_* values (include_numbers, include_symbols, include_characters) need to be true."
-            ))?;
+        BruteForceOptions {
+            minimum_size: mask.len(),
+            maximum_size: mask.len(),
+            include_numbers: false,
+            include_characters: false,
+            include_symbols: false,
+            custom_charset: None,
+            position_charsets: vec![],
+            mask: Some(mask),
         }
+        .validate()
+    }
 
-        Ok(BruteForceOptions {
-            maximum_size,
-            minimum_size,
-            include_characters,
-            include_numbers,
-            include_symbols,
-        })
+    /// Use the given charsets for the first positions of the password, e.g. `[Some("ABC"), None, Some("!?")]`
+    /// will search `A`, `B` or `C` as the first character, and `!` or `?` as the third character.
+    /// Duplicate characters are only searched once. Cannot be used with masks.
+    pub fn with_position_charsets(
+        mut self,
+        position_charsets: &[Option<&str>],
+    ) -> Result<Self, RamenskyError> {
+        self.position_charsets = position_charsets
+            .iter()
+            .map(|charset| charset.map(|c| unique_chars(c.chars())))
+            .collect();
+
+        self.validate()
+    }
+
+    fn validate(self) -> Result<Self, RamenskyError> {
+        let minimum_size = self.minimum_size;
+        let maximum_size = self.maximum_size;
+
+        if let Some(mask) = &self.mask {
+            if !self.position_charsets.is_empty() {
+                return Err(RamenskyError::InvalidConfiguration(format!(
+                    "position_charsets cannot be used with a mask ({}), use custom charsets in the mask instead.",
+                    mask.mask
+                )));
+            }
+        } else {
+            if minimum_size == maximum_size {
+                return Err(RamenskyError::InvalidConfiguration(format!(
+                    "minimum_size and maximum_size cannot be the same ({maximum_size})."
+                )));
+            }
+
+            if minimum_size >= maximum_size {
+                return Err(RamenskyError::InvalidConfiguration(format!(
+                    "minimum_size ({minimum_size} cannot be bigger than maximum_size ({maximum_size})."
+                )));
+            }
+
+            match &self.custom_charset {
+                Some(charset) if charset.is_empty() => {
+                    return Err(RamenskyError::InvalidConfiguration(
+                        "custom_charset cannot be empty.".to_string(),
+                    ));
+                }
+                Some(_) => {}
+                None if !self.include_numbers
+                    && !self.include_symbols
+                    && !self.include_characters =>
+                {
+                    return Err(RamenskyError::InvalidConfiguration(
+                        "At least one of the include_* values (include_numbers, include_symbols, \
+                         include_characters) need to be true."
+                            .to_string(),
+                    ));
+                }
+                None => {}
+            }
+        }
+
+        if self.position_charsets.len() > maximum_size {
+            return Err(RamenskyError::InvalidConfiguration(format!(
+                "position_charsets ({}) cannot be longer than maximum_size ({maximum_size}).",
+                self.position_charsets.len()
+            )));
+        }
+
+        if self
+            .position_charsets
+            .iter()
+            .flatten()
+            .any(|charset| charset.is_empty())
+        {
+            return Err(RamenskyError::InvalidConfiguration(
+                "position_charsets cannot contain empty charsets.".to_string(),
+            ));
+        }
+
+        if self.keyspace_size().is_none() {
+            return Err(RamenskyError::InvalidConfiguration(
+                "The keyspace is too big, try reducing maximum_size or the mask length."
+                    .to_string(),
+            ));
+        }
+
+        Ok(self)
+    }
+
+    /// The characters that will be used to generate passwords, in the order they will be tried.
+    pub fn charset(&self) -> Vec<char> {
+        if let Some(charset) = &self.custom_charset {
+            return charset.clone();
+        }
+
+        let mut charset = vec![];
+
+        if self.include_numbers {
+            charset.extend(NUMBERS.chars());
+        }
+
+        if self.include_characters {
+            charset.extend(LOWERCASE.chars());
+            charset.extend(UPPERCASE.chars());
+        }
+
+        if self.include_symbols {
+            charset.extend(SYMBOLS.chars());
+        }
+
+        charset
+    }
+
+    /// The characters that will be used for every position of the password, up to `maximum_size`.
+    pub fn charsets(&self) -> Vec<Vec<char>> {
+        if let Some(mask) = &self.mask {
+            return mask.positions().to_vec();
+        }
+
+        let charset = self.charset();
+
+        (0..self.maximum_size)
+            .map(|position| match self.position_charsets.get(position) {
+                Some(Some(position_charset)) => position_charset.clone(),
+                _ => charset.clone(),
+            })
+            .collect()
     }
+
+    /// Total amount of passwords that will be searched, or [`None`] if it does not fit in a `u128`.
+    pub fn keyspace_size(&self) -> Option<u128> {
+        let charsets = self.charsets();
+        let mut total: u128 = 0;
+
+        for size in self.minimum_size..=self.maximum_size {
+            let mut count: u128 = 1;
+
+            for charset in &charsets[..size] {
+                count = count.checked_mul(charset.len() as u128)?;
+            }
+
+            total = total.checked_add(count)?;
+        }
+
+        Some(total)
+    }
+}
+
+/// `BruteForceOptions` as they are saved in a configuration file. They are validated by building
+/// them with the same functions as in code.
+#[cfg(feature = "config")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BruteForceOptionsData {
+    minimum_size: usize,
+    maximum_size: usize,
+    include_numbers: bool,
+    include_characters: bool,
+    include_symbols: bool,
+    custom_charset: Option<String>,
+    position_charsets: Vec<String>,
+    mask: Option<String>,
+    mask_charsets: Vec<String>,
+}
+
+#[cfg(feature = "config")]
+impl Default for BruteForceOptionsData {
+    fn default() -> Self {
+        BruteForceOptions::default().into()
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<BruteForceOptions> for BruteForceOptionsData {
+    fn from(options: BruteForceOptions) -> Self {
+        BruteForceOptionsData {
+            minimum_size: options.minimum_size,
+            maximum_size: options.maximum_size,
+            include_numbers: options.include_numbers,
+            include_characters: options.include_characters,
+            include_symbols: options.include_symbols,
+            custom_charset: options.custom_charset.map(|c| c.into_iter().collect()),
+            position_charsets: options
+                .position_charsets
+                .into_iter()
+                .map(|c| c.map_or_else(String::new, |c| c.into_iter().collect()))
+                .collect(),
+            mask_charsets: options
+                .mask
+                .as_ref()
+                .map_or_else(Vec::new, |mask| mask.custom_charsets.clone()),
+            mask: options.mask.map(|mask| mask.mask),
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl TryFrom<BruteForceOptionsData> for BruteForceOptions {
+    type Error = RamenskyError;
+
+    fn try_from(data: BruteForceOptionsData) -> Result<Self, Self::Error> {
+        let options = match (&data.mask, &data.custom_charset) {
+            (Some(mask), _) => {
+                let charsets: Vec<&str> = data.mask_charsets.iter().map(String::as_str).collect();
+                Self::build_mask(mask, &charsets)?
+            }
+            (None, Some(charset)) => {
+                Self::build_custom(data.minimum_size, data.maximum_size, charset)?
+            }
+            (None, None) => Self::build(
+                data.minimum_size,
+                data.maximum_size,
+                data.include_numbers,
+                data.include_characters,
+                data.include_symbols,
+            )?,
+        };
+
+        if data.position_charsets.is_empty() {
+            return Ok(options);
+        }
+
+        let position_charsets: Vec<Option<&str>> = data
+            .position_charsets
+            .iter()
+            .map(|c| Some(c.as_str()).filter(|c| !c.is_empty()))
+            .collect();
+
+        options.with_position_charsets(&position_charsets)
+    }
+}
+
+/// The given characters, in order, without duplicates.
+pub(crate) fn unique_chars(characters: impl IntoIterator<Item = char>) -> Vec<char> {
+    let mut chars = vec![];
+
+    for c in characters {
+        if !chars.contains(&c) {
+            chars.push(c);
+        }
+    }
+
+    chars
 }
 
 #[cfg(test)]
@@ -75,5 +378,92 @@ mod tests {
         assert!(BruteForceOptions::build(2, 8, false, false, false).is_err());
         assert!(BruteForceOptions::build(2, 8, true, true, true).is_ok());
         assert!(BruteForceOptions::build(2, 8, true, false, true).is_ok());
+
+        // Keyspace validation
+        assert!(BruteForceOptions::build(1, 19, true, true, true).is_ok());
+        assert!(BruteForceOptions::build(1, 30, true, true, true).is_err());
+        assert!(BruteForceOptions::build_mask(&"?a".repeat(30), &[]).is_err());
+    }
+
+    #[test]
+    fn can_build_charset() {
+        let options = BruteForceOptions::build(1, 2, true, false, false).unwrap();
+        assert_eq!(options.charset().len(), 10);
+
+        let options = BruteForceOptions::build(1, 2, true, true, true).unwrap();
+        assert_eq!(options.charset().len(), 10 + 52 + 33);
+    }
+
+    #[test]
+    fn can_build_mask() {
+        let options = BruteForceOptions::build_mask("?u?l?1", &["!?d"]).unwrap();
+
+        assert_eq!(options.minimum_size, 3);
+        assert_eq!(options.maximum_size, 3);
+        assert_eq!(options.charsets()[2].len(), 11);
+
+        assert!(BruteForceOptions::build_mask("?u?l?1", &[]).is_err());
+    }
+
+    #[test]
+    fn can_build_custom_charset() {
+        let options = BruteForceOptions::build_custom(1, 2, "aäbäßç").unwrap();
+        assert_eq!(options.charset(), ['a', 'ä', 'b', 'ß', 'ç']);
+
+        assert!(BruteForceOptions::build_custom(1, 2, "").is_err());
+        assert!(BruteForceOptions::build_custom(2, 2, "abc").is_err());
+    }
+
+    #[test]
+    fn can_build_position_charsets() {
+        let options = BruteForceOptions::build(1, 3, true, false, false)
+            .unwrap()
+            .with_position_charsets(&[Some("AAB"), None, Some("ü")])
+            .unwrap();
+        let charsets = options.charsets();
+
+        assert_eq!(charsets[0], ['A', 'B']);
+        assert_eq!(charsets[1].len(), 10);
+        assert_eq!(charsets[2], ['ü']);
+        assert_eq!(options.keyspace_size(), Some(2 + 2 * 10 + 2 * 10));
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn can_serialize() {
+        let roundtrip = |options: BruteForceOptions| -> BruteForceOptions {
+            serde_json::from_str(&serde_json::to_string(&options).unwrap()).unwrap()
+        };
+
+        let options = roundtrip(BruteForceOptions::build_mask("?u?1", &["!#"]).unwrap());
+        assert_eq!(options.mask.unwrap().custom_charsets, ["!#"]);
+
+        let options = roundtrip(
+            BruteForceOptions::build_custom(1, 3, "abc")
+                .unwrap()
+                .with_position_charsets(&[None, Some("X")])
+                .unwrap(),
+        );
+        assert_eq!(
+            options.charsets(),
+            [vec!['a', 'b', 'c'], vec!['X'], vec!['a', 'b', 'c']]
+        );
+
+        assert!(serde_json::from_str::<BruteForceOptions>(r#"{"minimum_size": 11}"#).is_err());
+    }
+
+    #[test]
+    fn can_validate_position_charsets() {
+        let options = BruteForceOptions::build(1, 2, true, false, false).unwrap();
+
+        assert!(options
+            .clone()
+            .with_position_charsets(&[Some("a"), Some("b"), Some("c")])
+            .is_err());
+        assert!(options.with_position_charsets(&[Some("")]).is_err());
+        assert!(BruteForceOptions::build_mask("?d", &[])
+            .unwrap()
+            .with_position_charsets(&[Some("a")])
+            .is_err());
     }
 }
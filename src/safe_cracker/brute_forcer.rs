@@ -1,11 +1,344 @@
+//! Module containing the iterator that generates passwords by brute forcing.
+
+use crate::error::RamenskyError;
 use crate::safe_cracker::brute_force_options::BruteForceOptions;
 
+/// `BruteForcer` is an iterator that generates every possible password for the given
+/// [`BruteForceOptions`], starting from `minimum_size` up to and including `maximum_size`.
+/// For every size, the last character changes the fastest, e.g. `00`, `01`, ..., `99`.
+/// If a mask is provided, only the passwords matching the mask are generated.
+///
+/// Every password has a fixed index in the keyspace, so a brute forcing job can be split into
+/// disjoint slices with [`with_range()`](BruteForcer::with_range), e.g. to run on separate machines,
+/// or resumed from the last [`position()`](BruteForcer::position).
 pub struct BruteForcer {
-    options: BruteForceOptions,
+    /// Characters to use, for every position of the password.
+    charsets: Vec<Vec<char>>,
+    minimum_size: usize,
+    maximum_size: usize,
+    keyspace_size: u128,
+    /// Indexes to the charset, for every character of the next password.
+    indexes: Vec<usize>,
+    /// Index of the next password in the keyspace.
+    position: u128,
+    /// Index of the password to stop at. This password is not generated.
+    end: u128,
 }
 
 impl BruteForcer {
     pub fn new(options: BruteForceOptions) -> Self {
-        Self { options }
+        let charsets = options.charsets();
+        let keyspace_size = options.keyspace_size().unwrap_or(u128::MAX);
+
+        Self {
+            charsets,
+            minimum_size: options.minimum_size,
+            maximum_size: options.maximum_size,
+            keyspace_size,
+            indexes: vec![0; options.minimum_size],
+            position: 0,
+            end: keyspace_size,
+        }
+    }
+
+    /// Build a `BruteForcer` that only generates the passwords from index `start` up to, but not
+    /// including, index `end` of the keyspace.
+    pub fn with_range(
+        options: BruteForceOptions,
+        start: u128,
+        end: u128,
+    ) -> Result<Self, RamenskyError> {
+        let mut brute_forcer = Self::new(options);
+
+        if start > end {
+            return Err(RamenskyError::InvalidConfiguration(format!(
+                "start ({start}) cannot be bigger than end ({end})."
+            )));
+        }
+
+        if end > brute_forcer.keyspace_size {
+            return Err(RamenskyError::InvalidConfiguration(format!(
+                "end ({end}) cannot be bigger than the keyspace size ({}).",
+                brute_forcer.keyspace_size
+            )));
+        }
+
+        if let Some(indexes) = brute_forcer.indexes_at(start) {
+            brute_forcer.indexes = indexes;
+        }
+        brute_forcer.position = start;
+        brute_forcer.end = end;
+
+        Ok(brute_forcer)
+    }
+
+    /// Total amount of passwords in the keyspace, regardless of the range.
+    pub fn keyspace_size(&self) -> u128 {
+        self.keyspace_size
+    }
+
+    /// Index of the next password that will be generated. Can be used to resume later on with
+    /// [`with_range()`](BruteForcer::with_range).
+    pub fn position(&self) -> u128 {
+        self.position
+    }
+
+    /// Start over from the first password of the keyspace.
+    pub fn restart(&mut self) {
+        self.indexes = vec![0; self.minimum_size];
+        self.position = 0;
+        self.end = self.keyspace_size;
+    }
+
+    /// The password at the given index of the keyspace, or [`None`] if the index is out of bounds.
+    pub fn password_at(&self, index: u128) -> Option<String> {
+        self.indexes_at(index)
+            .map(|indexes| self.password_from(&indexes))
+    }
+
+    fn indexes_at(&self, mut index: u128) -> Option<Vec<usize>> {
+        if index >= self.keyspace_size {
+            return None;
+        }
+
+        for size in self.minimum_size..=self.maximum_size {
+            let count = self.charsets[..size].iter().fold(1u128, |acc, charset| {
+                acc.saturating_mul(charset.len() as u128)
+            });
+
+            if index < count {
+                let mut indexes = vec![0; size];
+
+                for (position, i) in indexes.iter_mut().enumerate().rev() {
+                    let charset_len = self.charsets[position].len() as u128;
+                    *i = (index % charset_len) as usize;
+                    index /= charset_len;
+                }
+
+                return Some(indexes);
+            }
+
+            index -= count;
+        }
+
+        None
+    }
+
+    fn password_from(&self, indexes: &[usize]) -> String {
+        indexes
+            .iter()
+            .enumerate()
+            .map(|(position, index)| self.charsets[position][*index])
+            .collect()
+    }
+
+    /// Write the next password into `buffer` as UTF-8, replacing its contents, so the same buffer can be
+    /// reused for every password. Returns false once every password has been generated.
+    pub fn next_into(&mut self, buffer: &mut Vec<u8>) -> bool {
+        if self.position >= self.end {
+            return false;
+        }
+
+        buffer.clear();
+
+        for (position, index) in self.indexes.iter().enumerate() {
+            let c = self.charsets[position][*index];
+            buffer.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+        self.advance();
+
+        true
+    }
+
+    /// Move on to the next password, like an odometer.
+    fn advance(&mut self) {
+        self.position += 1;
+
+        for (position, index) in self.indexes.iter_mut().enumerate().rev() {
+            *index += 1;
+
+            if *index < self.charsets[position].len() {
+                return;
+            }
+
+            *index = 0;
+        }
+
+        // Every combination for the current size has been generated.
+        if self.indexes.len() < self.maximum_size {
+            self.indexes.push(0);
+        }
+    }
+}
+
+impl Iterator for BruteForcer {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.end {
+            return None;
+        }
+
+        let password = self.password_from(&self.indexes);
+        self.advance();
+
+        Some(password)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::safe_cracker::brute_force_options::BruteForceOptions;
+    use crate::safe_cracker::brute_forcer::BruteForcer;
+
+    #[test]
+    fn can_generate_into_buffer() {
+        let options = BruteForceOptions::build(1, 2, true, true, false).unwrap();
+        let pws: Vec<String> = BruteForcer::new(options.clone()).collect();
+
+        let mut brute_forcer = BruteForcer::new(options);
+        let mut buffer = vec![];
+
+        for pw in pws {
+            assert!(brute_forcer.next_into(&mut buffer));
+            assert_eq!(buffer, pw.as_bytes());
+        }
+        assert!(!brute_forcer.next_into(&mut buffer));
+    }
+
+    #[test]
+    fn can_generate_in_order() {
+        let brute_forcer =
+            BruteForcer::new(BruteForceOptions::build(1, 2, true, false, false).unwrap());
+
+        let pws: Vec<String> = brute_forcer.collect();
+
+        assert_eq!(pws.len(), 10 + 100);
+        assert_eq!(pws[..3], ["0", "1", "2"]);
+        assert_eq!(pws[9..12], ["9", "00", "01"]);
+        assert_eq!(pws.last().unwrap(), "99");
+    }
+
+    #[test]
+    fn can_cover_whole_keyspace() {
+        let options = BruteForceOptions::build(1, 2, true, true, true).unwrap();
+        let charset_len = options.charset().len();
+
+        let pws: Vec<String> = BruteForcer::new(options).collect();
+        let unique: HashSet<&String> = pws.iter().collect();
+
+        assert_eq!(pws.len(), charset_len + charset_len.pow(2));
+        assert_eq!(unique.len(), pws.len());
+
+        for pw in ["a", "Z", "~", "a1", "Z~", "  "] {
+            assert!(unique.contains(&String::from(pw)));
+        }
+    }
+
+    #[test]
+    fn can_generate_multiple_sizes() {
+        let pws: Vec<String> =
+            BruteForcer::new(BruteForceOptions::build(2, 4, true, false, false).unwrap()).collect();
+
+        assert_eq!(pws.len(), 100 + 1_000 + 10_000);
+        assert_eq!(pws.first().unwrap(), "00");
+        assert_eq!(pws.last().unwrap(), "9999");
+        assert!(pws.iter().all(|pw| (2..=4).contains(&pw.len())));
+    }
+
+    #[test]
+    fn can_generate_from_mask() {
+        let options = BruteForceOptions::build_mask("?u?d!", &[]).unwrap();
+
+        let pws: Vec<String> = BruteForcer::new(options).collect();
+
+        assert_eq!(pws.len(), 26 * 10);
+        assert_eq!(pws[..2], ["A0!", "A1!"]);
+        assert_eq!(pws.last().unwrap(), "Z9!");
+    }
+
+    #[test]
+    fn can_generate_from_mask_with_custom_charsets() {
+        let options = BruteForceOptions::build_mask("Summer?1?2", &["ab", "!??"]).unwrap();
+
+        let pws: Vec<String> = BruteForcer::new(options).collect();
+
+        assert_eq!(pws, ["Summera!", "Summera?", "Summerb!", "Summerb?"]);
+    }
+
+    #[test]
+    fn can_calculate_keyspace_size() {
+        let brute_forcer =
+            BruteForcer::new(BruteForceOptions::build(1, 3, true, false, false).unwrap());
+        assert_eq!(brute_forcer.keyspace_size(), 10 + 100 + 1_000);
+
+        let brute_forcer = BruteForcer::new(BruteForceOptions::build_mask("?u?d!", &[]).unwrap());
+        assert_eq!(brute_forcer.keyspace_size(), 260);
+    }
+
+    #[test]
+    fn can_get_password_at_index() {
+        let options = BruteForceOptions::build(1, 3, true, true, false).unwrap();
+        let pws: Vec<String> = BruteForcer::new(options.clone()).collect();
+        let brute_forcer = BruteForcer::new(options);
+
+        for (index, pw) in pws.iter().enumerate() {
+            assert_eq!(brute_forcer.password_at(index as u128).as_ref(), Some(pw));
+        }
+
+        assert_eq!(brute_forcer.password_at(pws.len() as u128), None);
+    }
+
+    #[test]
+    fn can_generate_range() {
+        let options = BruteForceOptions::build(1, 2, true, false, false).unwrap();
+        let pws: Vec<String> = BruteForcer::with_range(options, 5, 15).unwrap().collect();
+
+        assert_eq!(pws, ["5", "6", "7", "8", "9", "00", "01", "02", "03", "04"]);
+    }
+
+    #[test]
+    fn can_split_into_disjoint_ranges() {
+        let options = BruteForceOptions::build(1, 3, true, false, true).unwrap();
+        let all: Vec<String> = BruteForcer::new(options.clone()).collect();
+        let keyspace_size = all.len() as u128;
+
+        let mut sliced = vec![];
+        let bounds = [0, 7, keyspace_size / 2, keyspace_size - 1, keyspace_size];
+
+        for window in bounds.windows(2) {
+            let mut brute_forcer =
+                BruteForcer::with_range(options.clone(), window[0], window[1]).unwrap();
+            sliced.extend(&mut brute_forcer);
+            assert_eq!(brute_forcer.position(), window[1]);
+        }
+
+        assert_eq!(sliced, all);
+    }
+
+    #[test]
+    fn can_validate_range() {
+        let options = BruteForceOptions::build(1, 2, true, false, false).unwrap();
+
+        assert!(BruteForcer::with_range(options.clone(), 10, 5).is_err());
+        assert!(BruteForcer::with_range(options.clone(), 0, 111).is_err());
+        assert!(BruteForcer::with_range(options, 110, 110)
+            .unwrap()
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn can_generate_from_custom_charsets() {
+        let options = BruteForceOptions::build_custom(1, 2, "äß")
+            .unwrap()
+            .with_position_charsets(&[Some("Çç")])
+            .unwrap();
+
+        let pws: Vec<String> = BruteForcer::new(options).collect();
+
+        assert_eq!(pws, ["Ç", "ç", "Çä", "Çß", "çä", "çß"]);
     }
 }
@@ -0,0 +1,48 @@
+//! Module containing the token that is used to stop `SafeCracker` from another thread.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// `CancellationToken` stops a running `SafeCracker`, when [`cancel()`](CancellationToken::cancel)
+/// is called. Clones of a token share the same state, so one clone can be given to
+/// [`SafeCracker::with_cancellation_token()`](crate::safe_cracker::safe_cracker::SafeCracker::with_cancellation_token),
+/// and the other one cancelled from another thread, e.g. a Ctrl-C handler.
+///
+/// Cancellation is cooperative: the passwords that are already being tried are finished first.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop the `SafeCracker` that uses this token, or any clone of it.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::safe_cracker::cancellation_token::CancellationToken;
+
+    #[test]
+    fn can_cancel_from_another_thread() {
+        let token = CancellationToken::new();
+        assert!(!token.is_cancelled());
+
+        let clone = token.clone();
+        thread::spawn(move || clone.cancel()).join().unwrap();
+
+        assert!(token.is_cancelled());
+    }
+}
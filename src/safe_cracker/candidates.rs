@@ -0,0 +1,887 @@
+//! Module containing the iterator that generates the passwords of every stage of the pipeline, in order.
+
+use std::mem;
+use std::time::{Duration, Instant};
+
+use crate::error::RamenskyError;
+use crate::safe_cracker::attack::{self, Attack, AttackStage};
+use crate::safe_cracker::brute_force_options::BruteForceOptions;
+use crate::safe_cracker::brute_forcer::BruteForcer;
+use crate::safe_cracker::checkpoint::Checkpoint;
+use crate::safe_cracker::password_reader::PasswordReader;
+use crate::safe_cracker::reader_configuration::PwListConfiguration;
+use crate::safe_cracker::rule::Rule;
+use crate::safe_cracker::statistics::PasswordSource;
+
+/// Where a generated password came from. Cheap to copy, unlike [`PasswordSource`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Origin {
+    /// Index of the stage in the pipeline.
+    pub stage: usize,
+    pub position: Position,
+}
+
+/// Where a generated password is in its stage.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Position {
+    /// Line `line` (starting at 1) of the password list, combined with the rule, password of the
+    /// `right` list, or password of the mask at `index`.
+    Line { line: usize, index: u128 },
+    /// Index in the brute forcing keyspace.
+    BruteForce(u128),
+}
+
+/// How every password of a password list is turned into the passwords of a stage.
+enum Mangling {
+    /// The passwords are used as they are.
+    None,
+    /// Every rule is applied to every password.
+    Rules(Vec<Rule>),
+    /// Every password of the `right` list, with its line, is appended to every password.
+    Combine {
+        path: String,
+        words: Vec<(Vec<u8>, usize)>,
+        skipped_lines: u64,
+        converted_lines: u64,
+    },
+    /// Every password of the mask is appended to every password, or prepended if `mask_first` is true.
+    Mask {
+        options: BruteForceOptions,
+        brute_forcer: Box<BruteForcer>,
+        mask_first: bool,
+        buffer: Vec<u8>,
+    },
+}
+
+impl Mangling {
+    /// Read the `right` list of a combinator into memory.
+    fn combine(right: &PwListConfiguration) -> Result<Self, RamenskyError> {
+        let mut reader = PasswordReader::build(vec![right.clone()])?;
+        let mut words = vec![];
+        let mut word = vec![];
+
+        while reader.next_into(&mut word) {
+            words.push((mem::take(&mut word), reader.line_offsets()[0]));
+        }
+
+        if let Some(e) = reader.take_error() {
+            return Err(e);
+        }
+
+        Ok(Mangling::Combine {
+            path: reader.paths()[0].clone(),
+            words,
+            skipped_lines: reader.skipped_lines(),
+            converted_lines: reader.converted_lines(),
+        })
+    }
+
+    fn mask(options: BruteForceOptions, mask_first: bool) -> Self {
+        Mangling::Mask {
+            brute_forcer: Box::new(BruteForcer::new(options.clone())),
+            options,
+            mask_first,
+            buffer: vec![],
+        }
+    }
+
+    /// Amount of passwords every password of the list is turned into.
+    fn len(&self) -> u128 {
+        match self {
+            Mangling::None => 1,
+            Mangling::Rules(rules) => rules.len() as u128,
+            Mangling::Combine { words, .. } => words.len() as u128,
+            Mangling::Mask { brute_forcer, .. } => brute_forcer.keyspace_size(),
+        }
+    }
+
+    /// Write the password at `index` for `word` into `buffer`. The passwords of masks are generated in
+    /// order, from the start of the keyspace after every [`restart()`](Mangling::restart).
+    fn apply(&mut self, word: &[u8], index: u128, buffer: &mut Vec<u8>) {
+        match self {
+            Mangling::None => {
+                buffer.clear();
+                buffer.extend_from_slice(word);
+            }
+            Mangling::Rules(rules) => rules[index as usize].apply_into(word, buffer),
+            Mangling::Combine { words, .. } => {
+                buffer.clear();
+                buffer.extend_from_slice(word);
+                buffer.extend_from_slice(&words[index as usize].0);
+            }
+            Mangling::Mask {
+                brute_forcer,
+                mask_first,
+                buffer: mask,
+                ..
+            } => {
+                brute_forcer.next_into(mask);
+                buffer.clear();
+
+                if *mask_first {
+                    buffer.extend_from_slice(mask);
+                    buffer.extend_from_slice(word);
+                } else {
+                    buffer.extend_from_slice(word);
+                    buffer.extend_from_slice(mask);
+                }
+            }
+        }
+    }
+
+    /// Start over for the next password of the list.
+    fn restart(&mut self) {
+        if let Mangling::Mask { brute_forcer, .. } = self {
+            brute_forcer.restart();
+        }
+    }
+}
+
+/// Generates the passwords of a stage.
+enum Generator {
+    /// Passwords of a password list, turned into the passwords of the stage by the mangling.
+    List {
+        reader: PasswordReader,
+        mangling: Mangling,
+        /// The password of the list that the mangling is being applied to.
+        word: Option<Vec<u8>>,
+        /// Index of the next password of the mangling for `word`.
+        index: u128,
+    },
+    BruteForce {
+        options: BruteForceOptions,
+        brute_forcer: BruteForcer,
+    },
+}
+
+impl Generator {
+    fn build(attack: &Attack) -> Result<Self, RamenskyError> {
+        match attack {
+            Attack::Wordlist { list } => Self::list(list, Mangling::None),
+            Attack::Rules { list, rules_path } => {
+                Self::list(list, Mangling::Rules(Rule::load(rules_path)?))
+            }
+            Attack::Mask {
+                mask,
+                mask_charsets,
+            } => Ok(Self::brute_force(attack::mask_options(
+                mask,
+                mask_charsets,
+            )?)),
+            Attack::BruteForce { options } => Ok(Self::brute_force(options.clone())),
+            Attack::Combinator { left, right } => Self::list(left, Mangling::combine(right)?),
+            Attack::Hybrid {
+                list,
+                mask,
+                mask_charsets,
+                mask_first,
+            } => {
+                let options = attack::mask_options(mask, mask_charsets)?;
+                Self::list(list, Mangling::mask(options, *mask_first))
+            }
+        }
+    }
+
+    fn list(list: &PwListConfiguration, mangling: Mangling) -> Result<Self, RamenskyError> {
+        Ok(Generator::List {
+            reader: PasswordReader::build(vec![list.clone()])?,
+            mangling,
+            word: None,
+            index: 0,
+        })
+    }
+
+    fn brute_force(options: BruteForceOptions) -> Self {
+        Generator::BruteForce {
+            brute_forcer: BruteForcer::new(options.clone()),
+            options,
+        }
+    }
+
+    fn next_into(&mut self, buffer: &mut Vec<u8>) -> Option<Position> {
+        match self {
+            Generator::List {
+                reader,
+                mangling: Mangling::None,
+                ..
+            } => reader.next_into(buffer).then(|| Position::Line {
+                line: reader.line_offsets()[0],
+                index: 0,
+            }),
+            Generator::List {
+                reader,
+                mangling,
+                word,
+                index,
+            } => loop {
+                if let Some(word) = word {
+                    if *index < mangling.len() {
+                        mangling.apply(word, *index, buffer);
+                        *index += 1;
+
+                        return Some(Position::Line {
+                            line: reader.line_offsets()[0],
+                            index: *index - 1,
+                        });
+                    }
+                }
+
+                let mut next = word.take().unwrap_or_default();
+
+                if !reader.next_into(&mut next) {
+                    return None;
+                }
+
+                *word = Some(next);
+                *index = 0;
+                mangling.restart();
+            },
+            Generator::BruteForce { brute_forcer, .. } => brute_forcer
+                .next_into(buffer)
+                .then(|| Position::BruteForce(BruteForcer::position(brute_forcer) - 1)),
+        }
+    }
+
+    fn reader(&self) -> Option<&PasswordReader> {
+        match self {
+            Generator::List { reader, .. } => Some(reader),
+            Generator::BruteForce { .. } => None,
+        }
+    }
+}
+
+/// A stage of the pipeline, and how much of its budget it used.
+struct Stage {
+    attack: Attack,
+    generator: Generator,
+    max_attempts: Option<u64>,
+    max_duration: Option<Duration>,
+    /// Amount of passwords generated so far.
+    generated: u64,
+    /// When the stage was started.
+    started: Option<Instant>,
+}
+
+impl Stage {
+    fn build(stage: AttackStage) -> Result<Self, RamenskyError> {
+        Ok(Stage {
+            generator: Generator::build(&stage.attack)?,
+            attack: stage.attack,
+            max_attempts: stage.max_attempts,
+            max_duration: stage.max_duration,
+            generated: 0,
+            started: None,
+        })
+    }
+
+    fn next_into(&mut self, buffer: &mut Vec<u8>) -> Option<Position> {
+        let started = *self.started.get_or_insert_with(Instant::now);
+
+        if self.max_attempts.is_some_and(|max| self.generated >= max)
+            || self
+                .max_duration
+                .is_some_and(|max| started.elapsed() >= max)
+        {
+            return None;
+        }
+
+        let position = self.generator.next_into(buffer)?;
+        self.generated += 1;
+
+        Some(position)
+    }
+
+    /// Whether the stage is known to be finished. Password lists are only finished once they are read
+    /// past their last line.
+    fn is_done(&self) -> bool {
+        let exhausted = match &self.generator {
+            Generator::BruteForce { brute_forcer, .. } => {
+                brute_forcer.position() >= brute_forcer.keyspace_size()
+            }
+            Generator::List { .. } => false,
+        };
+
+        exhausted || self.max_attempts.is_some_and(|max| self.generated >= max)
+    }
+
+    /// Checkpoint of the next password of the stage, at the given index.
+    fn checkpoint(&self, index: usize) -> Checkpoint {
+        let mut checkpoint = Checkpoint {
+            stage: Some(index),
+            stage_attempts: self.generated,
+            line_offsets: vec![],
+            rule_index: 0,
+            brute_force_index: 0,
+        };
+
+        match &self.generator {
+            Generator::List {
+                reader,
+                mangling,
+                word,
+                index,
+            } => {
+                checkpoint.line_offsets = reader.line_offsets().to_vec();
+
+                // The mangling has not been applied completely to the word yet, so it is not done.
+                if word.is_some() && *index < mangling.len() {
+                    checkpoint.line_offsets[0] -= 1;
+
+                    match mangling {
+                        Mangling::Mask { .. } => checkpoint.brute_force_index = *index,
+                        _ => checkpoint.rule_index = *index as usize,
+                    }
+                }
+            }
+            Generator::BruteForce { brute_forcer, .. } => {
+                checkpoint.brute_force_index = brute_forcer.position();
+            }
+        }
+
+        checkpoint
+    }
+
+    /// Skip every password of the stage before the given checkpoint.
+    fn resume(&mut self, checkpoint: &Checkpoint) -> Result<(), RamenskyError> {
+        let mismatch = || {
+            RamenskyError::InvalidConfiguration(format!(
+                "Checkpoint does not match stage {} ({}) of the pipeline.",
+                checkpoint.stage.unwrap_or_default(),
+                self.attack
+            ))
+        };
+
+        match &mut self.generator {
+            Generator::List {
+                reader,
+                mangling,
+                word,
+                index,
+            } => {
+                reader.skip_lines(&checkpoint.line_offsets)?;
+
+                let (resumed, unused) = match mangling {
+                    Mangling::Mask { .. } => {
+                        (checkpoint.brute_force_index, checkpoint.rule_index as u128)
+                    }
+                    _ => (checkpoint.rule_index as u128, checkpoint.brute_force_index),
+                };
+
+                if unused > 0 || (resumed > 0 && resumed >= mangling.len()) {
+                    return Err(mismatch());
+                }
+
+                if resumed > 0 {
+                    // The next word is the one the mangling was being applied to.
+                    *word = reader.next();
+                    *index = resumed;
+
+                    if let Mangling::Mask {
+                        options,
+                        brute_forcer,
+                        ..
+                    } = mangling
+                    {
+                        let keyspace_size = brute_forcer.keyspace_size();
+                        **brute_forcer =
+                            BruteForcer::with_range(options.clone(), resumed, keyspace_size)?;
+                    }
+                }
+            }
+            Generator::BruteForce {
+                options,
+                brute_forcer,
+            } => {
+                if !checkpoint.line_offsets.is_empty() || checkpoint.rule_index > 0 {
+                    return Err(mismatch());
+                }
+
+                let keyspace_size = brute_forcer.keyspace_size();
+                *brute_forcer = BruteForcer::with_range(
+                    options.clone(),
+                    checkpoint.brute_force_index,
+                    keyspace_size,
+                )?;
+            }
+        }
+
+        self.generated = checkpoint.stage_attempts;
+
+        Ok(())
+    }
+}
+
+/// `Candidates` runs the stages of the pipeline in order, and keeps track of where it is,
+/// so a [`Checkpoint`] can be taken at any time.
+/// A stage ends once all of its passwords are generated, or its budget is used up.
+/// Passwords are generated as bytes, see [`PasswordReader`].
+pub(crate) struct Candidates {
+    stages: Vec<Stage>,
+    /// Index of the stage that is generating passwords.
+    current: usize,
+    /// Origin of the last generated password.
+    origin: Option<Origin>,
+}
+
+impl Candidates {
+    pub fn build(pipeline: Vec<AttackStage>) -> Result<Self, RamenskyError> {
+        Ok(Self {
+            stages: pipeline
+                .into_iter()
+                .map(Stage::build)
+                .collect::<Result<_, _>>()?,
+            current: 0,
+            origin: None,
+        })
+    }
+
+    /// Skip every password before the given checkpoint.
+    pub fn resume(&mut self, checkpoint: &Checkpoint) -> Result<(), RamenskyError> {
+        match checkpoint.stage {
+            Some(stage) if stage >= self.stages.len() => {
+                Err(RamenskyError::InvalidConfiguration(format!(
+                    "Checkpoint contains stage {stage}, but the pipeline only has {} stages.",
+                    self.stages.len()
+                )))
+            }
+            Some(stage) => {
+                self.stages[stage].resume(checkpoint)?;
+                self.current = stage;
+                Ok(())
+            }
+            None => {
+                self.current = self.stages.len();
+                Ok(())
+            }
+        }
+    }
+
+    /// Checkpoint of the next password that will be generated.
+    pub fn checkpoint(&self) -> Checkpoint {
+        let next = self
+            .stages
+            .iter()
+            .enumerate()
+            .skip(self.current)
+            .find(|(_, stage)| !stage.is_done());
+
+        match next {
+            Some((index, stage)) => stage.checkpoint(index),
+            None => Checkpoint {
+                stage: None,
+                stage_attempts: 0,
+                line_offsets: vec![],
+                rule_index: 0,
+                brute_force_index: 0,
+            },
+        }
+    }
+
+    /// The error that stopped the password lists, if any. The next stages do not start after an error.
+    pub fn take_error(&mut self) -> Option<RamenskyError> {
+        match &mut self.stages.get_mut(self.current)?.generator {
+            Generator::List { reader, .. } => reader.take_error(),
+            Generator::BruteForce { .. } => None,
+        }
+    }
+
+    /// Origin of the last generated password, or [`None`] if no password was generated yet.
+    pub fn origin(&self) -> Option<Origin> {
+        self.origin
+    }
+
+    /// Stage of the last generated password, or [`None`] if no password was generated yet.
+    pub fn stage(&self) -> Option<usize> {
+        self.origin.map(|origin| origin.stage)
+    }
+
+    /// Amount of stages in the pipeline.
+    pub fn stage_count(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// The attack of the stage at the given index.
+    pub fn attack(&self, stage: usize) -> &Attack {
+        &self.stages[stage].attack
+    }
+
+    pub fn source_of(&self, origin: Origin) -> PasswordSource {
+        let stage = origin.stage;
+
+        match (&self.stages[stage].generator, origin.position) {
+            (
+                Generator::List {
+                    reader, mangling, ..
+                },
+                Position::Line { line, index },
+            ) => {
+                let path = reader.paths()[0].clone();
+
+                match mangling {
+                    Mangling::None => PasswordSource::PasswordList {
+                        stage,
+                        path,
+                        line,
+                        rule_index: None,
+                    },
+                    Mangling::Rules(_) => PasswordSource::PasswordList {
+                        stage,
+                        path,
+                        line,
+                        rule_index: Some(index as usize),
+                    },
+                    Mangling::Combine {
+                        path: right_path,
+                        words,
+                        ..
+                    } => PasswordSource::Combinator {
+                        stage,
+                        path,
+                        line,
+                        right_path: right_path.clone(),
+                        right_line: words[index as usize].1,
+                    },
+                    Mangling::Mask { .. } => PasswordSource::Hybrid {
+                        stage,
+                        path,
+                        line,
+                        mask_index: index,
+                    },
+                }
+            }
+            (_, Position::BruteForce(index)) => PasswordSource::BruteForce { stage, index },
+            (Generator::BruteForce { .. }, Position::Line { .. }) => {
+                unreachable!("Only password lists generate lines.")
+            }
+        }
+    }
+
+    /// Amount of lines of the password lists that were skipped, because they were not valid UTF-8.
+    pub fn skipped_lines(&self) -> u64 {
+        self.stages
+            .iter()
+            .map(|stage| match &stage.generator {
+                Generator::List {
+                    mangling: Mangling::Combine { skipped_lines, .. },
+                    reader,
+                    ..
+                } => reader.skipped_lines() + skipped_lines,
+                generator => generator.reader().map_or(0, PasswordReader::skipped_lines),
+            })
+            .sum()
+    }
+
+    /// Amount of lines of the password lists that were converted to UTF-8.
+    pub fn converted_lines(&self) -> u64 {
+        self.stages
+            .iter()
+            .map(|stage| match &stage.generator {
+                Generator::List {
+                    mangling:
+                        Mangling::Combine {
+                            converted_lines, ..
+                        },
+                    reader,
+                    ..
+                } => reader.converted_lines() + converted_lines,
+                generator => generator
+                    .reader()
+                    .map_or(0, PasswordReader::converted_lines),
+            })
+            .sum()
+    }
+
+    /// Amount of passwords left to generate, if known. The password lists are not counted upfront,
+    /// so this is only known once the stages with password lists are finished.
+    pub fn remaining(&self) -> Option<u128> {
+        let mut remaining: u128 = 0;
+
+        for stage in self.stages.iter().skip(self.current) {
+            let left = match &stage.generator {
+                Generator::BruteForce { brute_forcer, .. } => {
+                    brute_forcer.keyspace_size() - brute_forcer.position()
+                }
+                Generator::List { .. } => return None,
+            };
+            let budget = stage
+                .max_attempts
+                .map_or(u128::MAX, |max| max.saturating_sub(stage.generated) as u128);
+
+            remaining = remaining.saturating_add(left.min(budget));
+        }
+
+        Some(remaining)
+    }
+
+    /// Generate the next password into `buffer`, replacing its contents, so the same buffer can be reused
+    /// for every password. Returns false once every password has been generated.
+    pub fn next_into(&mut self, buffer: &mut Vec<u8>) -> bool {
+        while let Some(stage) = self.stages.get_mut(self.current) {
+            if let Some(position) = stage.next_into(buffer) {
+                self.origin = Some(Origin {
+                    stage: self.current,
+                    position,
+                });
+                return true;
+            }
+
+            if stage
+                .generator
+                .reader()
+                .is_some_and(PasswordReader::has_error)
+            {
+                return false;
+            }
+
+            self.current += 1;
+        }
+
+        false
+    }
+}
+
+impl Iterator for Candidates {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut password = vec![];
+        self.next_into(&mut password).then_some(password)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::safe_cracker::attack::{Attack, AttackStage};
+    use crate::safe_cracker::brute_force_options::BruteForceOptions;
+    use crate::safe_cracker::candidates::{Candidates, Origin, Position};
+    use crate::safe_cracker::reader_configuration::PwListConfiguration;
+    use crate::safe_cracker::statistics::PasswordSource;
+
+    const PW_LIST: &str = "tests/resources/pw-list.txt";
+    const PW_LIST_2: &str = "tests/resources/pw-list-2.txt";
+
+    fn strings(pws: impl Iterator<Item = Vec<u8>>) -> Vec<String> {
+        pws.map(|pw| String::from_utf8(pw).unwrap()).collect()
+    }
+
+    fn list(path: &str) -> PwListConfiguration {
+        PwListConfiguration::new(path, None)
+    }
+
+    fn brute_force() -> AttackStage {
+        AttackStage::new(Attack::BruteForce {
+            options: BruteForceOptions::build(0, 1, true, false, false).unwrap(),
+        })
+    }
+
+    fn candidates(pipeline: &[AttackStage]) -> Candidates {
+        Candidates::build(pipeline.to_vec()).unwrap()
+    }
+
+    /// Every kind of attack, with the passwords of tests/resources.
+    fn pipeline() -> Vec<AttackStage> {
+        vec![
+            AttackStage::new(Attack::Rules {
+                list: list(PW_LIST),
+                rules_path: "tests/resources/rules.txt".into(),
+            }),
+            AttackStage::new(Attack::Combinator {
+                left: list(PW_LIST_2),
+                right: list(PW_LIST),
+            }),
+            AttackStage::new(Attack::Hybrid {
+                list: list(PW_LIST_2),
+                mask: "?1".to_string(),
+                mask_charsets: vec!["!#".to_string()],
+                mask_first: true,
+            }),
+            AttackStage::new(Attack::Mask {
+                mask: "?d".to_string(),
+                mask_charsets: vec![],
+            }),
+            AttackStage::new(Attack::Wordlist {
+                list: list(PW_LIST),
+            }),
+        ]
+    }
+
+    #[test]
+    fn can_chain_stages() {
+        let pws = strings(candidates(&[
+            AttackStage::new(Attack::Wordlist {
+                list: list(PW_LIST),
+            }),
+            brute_force(),
+        ]));
+
+        assert_eq!(pws.len(), 3 + 11);
+        assert_eq!(pws[..4], ["test1", "test2", "test3", ""]);
+    }
+
+    #[test]
+    fn can_run_every_attack() {
+        let pws = strings(candidates(&pipeline()));
+
+        assert_eq!(pws.len(), 3 * 4 + 3 * 3 + 3 * 2 + 10 + 3);
+        assert_eq!(pws[..4], ["test1", "Test1", "Test11!", "test1"]);
+        assert_eq!(pws[12..15], ["alphatest1", "alphatest2", "alphatest3"]);
+        assert_eq!(pws[21..23], ["!alpha", "#alpha"]);
+        assert_eq!(pws[27..29], ["0", "1"]);
+        assert_eq!(pws[37..], ["test1", "test2", "test3"]);
+    }
+
+    #[test]
+    fn can_use_budgets() {
+        let pws = strings(candidates(&[
+            AttackStage::new(Attack::Wordlist {
+                list: list(PW_LIST),
+            })
+            .with_max_attempts(2),
+            brute_force().with_max_duration(Duration::ZERO),
+            AttackStage::new(Attack::Wordlist {
+                list: list(PW_LIST_2),
+            }),
+        ]));
+
+        assert_eq!(pws, ["test1", "test2", "alpha", "beta", "gamma"]);
+    }
+
+    #[test]
+    fn can_resume_from_checkpoint() {
+        let pipeline = pipeline();
+        let all = strings(candidates(&pipeline));
+
+        for skip in 0..=all.len() {
+            let mut first_run = candidates(&pipeline);
+            first_run.by_ref().take(skip).for_each(drop);
+            let checkpoint = first_run.checkpoint();
+
+            let mut second_run = candidates(&pipeline);
+            second_run.resume(&checkpoint).unwrap();
+
+            assert_eq!(strings(second_run), all[skip..], "Skipped {skip}");
+        }
+    }
+
+    #[test]
+    fn can_resume_budgets() {
+        let pipeline = [brute_force().with_max_attempts(5)];
+
+        let mut first_run = candidates(&pipeline);
+        first_run.by_ref().take(3).for_each(drop);
+
+        let mut second_run = candidates(&pipeline);
+        second_run.resume(&first_run.checkpoint()).unwrap();
+
+        assert_eq!(strings(second_run), ["2", "3"]);
+    }
+
+    #[test]
+    fn can_take_checkpoints() {
+        let mut candidates = candidates(&pipeline());
+
+        candidates.by_ref().take(6).for_each(drop);
+        let checkpoint = candidates.checkpoint();
+        assert_eq!(checkpoint.stage, Some(0));
+        assert_eq!(checkpoint.stage_attempts, 6);
+        assert_eq!(checkpoint.line_offsets, [1]);
+        assert_eq!(checkpoint.rule_index, 2);
+
+        candidates.by_ref().take(6 + 9 + 3).for_each(drop);
+        let checkpoint = candidates.checkpoint();
+        assert_eq!(checkpoint.stage, Some(2));
+        assert_eq!(checkpoint.line_offsets, [1]);
+        assert_eq!(checkpoint.rule_index, 0);
+        assert_eq!(checkpoint.brute_force_index, 1);
+
+        candidates.by_ref().for_each(drop);
+        assert_eq!(candidates.checkpoint().stage, None);
+    }
+
+    #[test]
+    fn should_validate_checkpoints() {
+        let mut checkpoint = candidates(&pipeline()).checkpoint();
+        checkpoint.stage = Some(5);
+        assert!(candidates(&pipeline()).resume(&checkpoint).is_err());
+
+        checkpoint.stage = Some(0);
+        checkpoint.rule_index = 4;
+        assert!(candidates(&pipeline()).resume(&checkpoint).is_err());
+
+        checkpoint.stage = Some(3);
+        checkpoint.rule_index = 0;
+        assert!(candidates(&pipeline()).resume(&checkpoint).is_err());
+    }
+
+    #[test]
+    fn can_track_origins() {
+        let mut candidates = candidates(&pipeline());
+        assert_eq!(candidates.origin(), None);
+
+        candidates.by_ref().take(6).for_each(drop);
+        let origin = candidates.origin().unwrap();
+        assert_eq!(
+            origin,
+            Origin {
+                stage: 0,
+                position: Position::Line { line: 2, index: 1 },
+            }
+        );
+        assert_eq!(
+            candidates.source_of(origin),
+            PasswordSource::PasswordList {
+                stage: 0,
+                path: PW_LIST.to_string(),
+                line: 2,
+                rule_index: Some(1),
+            }
+        );
+
+        candidates.by_ref().take(8).for_each(drop);
+        assert_eq!(
+            candidates.source_of(candidates.origin().unwrap()),
+            PasswordSource::Combinator {
+                stage: 1,
+                path: PW_LIST_2.to_string(),
+                line: 1,
+                right_path: PW_LIST.to_string(),
+                right_line: 2,
+            }
+        );
+
+        candidates.by_ref().take(10).for_each(drop);
+        assert_eq!(
+            candidates.source_of(candidates.origin().unwrap()),
+            PasswordSource::Hybrid {
+                stage: 2,
+                path: PW_LIST_2.to_string(),
+                line: 2,
+                mask_index: 0,
+            }
+        );
+
+        candidates.by_ref().take(6).for_each(drop);
+        assert_eq!(candidates.stage(), Some(3));
+        assert_eq!(
+            candidates.source_of(candidates.origin().unwrap()),
+            PasswordSource::BruteForce { stage: 3, index: 2 }
+        );
+    }
+
+    #[test]
+    fn can_count_remaining() {
+        let mut candidates = candidates(&[
+            AttackStage::new(Attack::Wordlist {
+                list: list(PW_LIST),
+            }),
+            brute_force().with_max_attempts(5),
+            brute_force(),
+        ]);
+        assert_eq!(candidates.remaining(), None);
+
+        candidates.by_ref().take(4).for_each(drop);
+        assert_eq!(candidates.remaining(), Some(4 + 11));
+    }
+}
@@ -0,0 +1,207 @@
+//! Module containing the checkpoints, that record how far `SafeCracker` got, so it can be resumed later on.
+//!
+//! Checkpoints are saved as plain text files:
+//! ```text
+//! stage=1
+//! stage_attempts=1532
+//! line_offsets=1532
+//! rule_index=3
+//! brute_force_index=0
+//! ```
+
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::error::RamenskyError;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Index of the stage of the [`pipeline`](crate::safe_cracker::options::Options::pipeline) that was
+    /// running when the checkpoint was taken, or [`None`] if every stage was finished.
+    pub stage: Option<usize>,
+
+    /// Amount of passwords the stage generated so far, which count towards its budget.
+    pub stage_attempts: u64,
+
+    /// Amount of lines already tried from the password list of the stage, or from the `left` list of a
+    /// [`Combinator`](crate::safe_cracker::attack::Attack::Combinator). Empty for stages without a password list.
+    pub line_offsets: Vec<usize>,
+
+    /// Index of the next rule, or password of the `right` list, to combine with the password at the line offset.
+    pub rule_index: usize,
+
+    /// Index of the next password to try in the brute forcing keyspace, or in the keyspace of the mask of a
+    /// [`Hybrid`](crate::safe_cracker::attack::Attack::Hybrid) that is combined with the password at the line offset.
+    pub brute_force_index: u128,
+}
+
+impl Checkpoint {
+    /// Load a checkpoint that was saved with [`save()`](Checkpoint::save).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RamenskyError> {
+        let contents = fs::read_to_string(&path).map_err(|e| RamenskyError::io(&path, e))?;
+        let path = path.as_ref().display();
+
+        let mut stage = None;
+        let mut stage_attempts = 0;
+        let mut line_offsets = None;
+        let mut rule_index = 0;
+        let mut brute_force_index = None;
+
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, value) = match line.split_once('=') {
+                Some(pair) => pair,
+                None => {
+                    return Err(RamenskyError::InvalidConfiguration(format!(
+                        "Invalid line in checkpoint {path}: {line}"
+                    )))
+                }
+            };
+
+            match key.trim() {
+                "stage" => {
+                    let value = value.trim();
+                    stage = Some(if value.is_empty() {
+                        None
+                    } else {
+                        Some(value.parse().map_err(|e| invalid_value(&path, key, e))?)
+                    });
+                }
+                "stage_attempts" => {
+                    stage_attempts = value
+                        .trim()
+                        .parse()
+                        .map_err(|e| invalid_value(&path, key, e))?
+                }
+                "line_offsets" => {
+                    line_offsets = Some(
+                        value
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|offset| !offset.is_empty())
+                            .map(usize::from_str)
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err(|e| invalid_value(&path, key, e))?,
+                    );
+                }
+                "rule_index" => {
+                    rule_index = value
+                        .trim()
+                        .parse()
+                        .map_err(|e| invalid_value(&path, key, e))?
+                }
+                "brute_force_index" => {
+                    brute_force_index = Some(
+                        value
+                            .trim()
+                            .parse()
+                            .map_err(|e| invalid_value(&path, key, e))?,
+                    )
+                }
+                _ => {
+                    return Err(RamenskyError::InvalidConfiguration(format!(
+                        "Unknown key in checkpoint {path}: {key}"
+                    )))
+                }
+            }
+        }
+
+        match (stage, line_offsets, brute_force_index) {
+            (Some(stage), Some(line_offsets), Some(brute_force_index)) => Ok(Checkpoint {
+                stage,
+                stage_attempts,
+                line_offsets,
+                rule_index,
+                brute_force_index,
+            }),
+            _ => Err(RamenskyError::InvalidConfiguration(format!(
+                "Checkpoint {path} needs to contain stage, line_offsets and brute_force_index."
+            ))),
+        }
+    }
+
+    /// Save the checkpoint to the given path. The file is replaced atomically, so a crash while
+    /// saving does not corrupt the previous checkpoint.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RamenskyError> {
+        let path = path.as_ref();
+        let stage = self.stage.map(|s| s.to_string()).unwrap_or_default();
+        let line_offsets = self
+            .line_offsets
+            .iter()
+            .map(|offset| offset.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let contents = format!(
+            "stage={stage}\nstage_attempts={}\nline_offsets={line_offsets}\nrule_index={}\nbrute_force_index={}\n",
+            self.stage_attempts, self.rule_index, self.brute_force_index
+        );
+
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, contents).map_err(|e| RamenskyError::io(&tmp_path, e))?;
+        fs::rename(&tmp_path, path).map_err(|e| RamenskyError::io(path, e))?;
+
+        Ok(())
+    }
+}
+
+fn invalid_value(path: &impl Display, key: &str, e: impl Display) -> RamenskyError {
+    RamenskyError::InvalidConfiguration(format!("Invalid {key} in checkpoint {path}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use crate::safe_cracker::checkpoint::Checkpoint;
+
+    #[test]
+    fn can_save_and_load() {
+        let path = env::temp_dir().join("ramensky-checkpoint-test.txt");
+        let path = path.to_str().unwrap();
+
+        let checkpoint = Checkpoint {
+            stage: Some(2),
+            stage_attempts: 1_000_012,
+            line_offsets: vec![1_000_000],
+            rule_index: 3,
+            brute_force_index: u128::MAX,
+        };
+
+        checkpoint.save(path).unwrap();
+        assert_eq!(Checkpoint::load(path).unwrap(), checkpoint);
+
+        let finished = Checkpoint {
+            stage: None,
+            stage_attempts: 0,
+            line_offsets: vec![],
+            rule_index: 0,
+            brute_force_index: 0,
+        };
+
+        finished.save(path).unwrap();
+        assert_eq!(Checkpoint::load(path).unwrap(), finished);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn can_validate() {
+        let path = env::temp_dir().join("ramensky-checkpoint-invalid-test.txt");
+        let path = path.to_str().unwrap();
+
+        fs::write(path, "stage=1\nline_offsets=1,2\n").unwrap();
+        assert!(Checkpoint::load(path).is_err());
+
+        fs::write(path, "stage=unknown\nline_offsets=\nbrute_force_index=0\n").unwrap();
+        assert!(Checkpoint::load(path).is_err());
+
+        fs::write(path, "stage=0\nline_offsets=a\nbrute_force_index=0\n").unwrap();
+        assert!(Checkpoint::load(path).is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,25 +1,32 @@
-use std::error::Error;
+use crate::error::RamenskyError;
 
 const MAX_AMOUNT: usize = 10_000_000;
 
 #[derive(Copy, Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "CommonPwListOptionsData")
+)]
 pub struct CommonPwListOptions {
     /// How many of the common password list to use. The maximum amount is 10 million.
     /// Set as [`None`](Option::None) to use the whole list.
     pub amount_to_use: Option<usize>,
 }
 
-impl CommonPwListOptions {
-    pub fn default() -> Self {
+impl Default for CommonPwListOptions {
+    fn default() -> Self {
         Self::build(Some(1_000_000)).unwrap()
     }
+}
 
-    pub fn build(amount_to_use: Option<usize>) -> Result<Self, Box<dyn Error>> {
+impl CommonPwListOptions {
+    pub fn build(amount_to_use: Option<usize>) -> Result<Self, RamenskyError> {
         if let Some(amount) = amount_to_use {
             if amount >= MAX_AMOUNT {
-                return Err(format!(
+                return Err(RamenskyError::InvalidConfiguration(format!(
                     "amount_to_use ({amount}) cannot be higher than {MAX_AMOUNT}."
-                ))?;
+                )));
             }
         }
 
@@ -27,6 +34,23 @@ impl CommonPwListOptions {
     }
 }
 
+/// `CommonPwListOptions` read from a configuration file, before they are validated.
+#[cfg(feature = "config")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CommonPwListOptionsData {
+    amount_to_use: Option<usize>,
+}
+
+#[cfg(feature = "config")]
+impl TryFrom<CommonPwListOptionsData> for CommonPwListOptions {
+    type Error = RamenskyError;
+
+    fn try_from(data: CommonPwListOptionsData) -> Result<Self, Self::Error> {
+        Self::build(data.amount_to_use)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::safe_cracker::common_pw_list_options::CommonPwListOptions;
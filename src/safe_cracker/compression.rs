@@ -0,0 +1,112 @@
+//! Module containing the decompression of password lists, used by the `PasswordReader`.
+//!
+//! The compression is detected from the first bytes of the file, so the file extension does not matter.
+//! Every compression needs its cargo feature to be enabled: `gzip`, `bzip2`, `xz` or `zstd`
+//! (or `compression` for all of them).
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::error::RamenskyError;
+
+/// Compressions that password lists can be read from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Compression {
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+impl Compression {
+    /// Detect the compression from the first bytes of a file, or [`None`] if it is not compressed.
+    pub fn detect(header: &[u8]) -> Option<Self> {
+        if header.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if header.starts_with(b"BZh") {
+            Some(Compression::Bzip2)
+        } else if header.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// Name of the cargo feature that enables the compression.
+    fn feature(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Bzip2 => "bzip2",
+            Compression::Xz => "xz",
+            Compression::Zstd => "zstd",
+        }
+    }
+}
+
+/// Open the password list at `path`, decompressing it while it is read if it is compressed.
+pub(crate) fn open(path: &Path) -> Result<Box<dyn BufRead + Send>, RamenskyError> {
+    let file = File::open(path).map_err(|e| RamenskyError::io(path, e))?;
+    let mut reader = BufReader::new(file);
+
+    // Peek at the first bytes, without consuming them.
+    let header = reader.fill_buf().map_err(|e| RamenskyError::io(path, e))?;
+
+    let compression = match Compression::detect(header) {
+        Some(compression) => compression,
+        None => return Ok(Box::new(reader)),
+    };
+
+    match compression {
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(BufReader::new(
+            flate2::bufread::MultiGzDecoder::new(reader),
+        ))),
+        #[cfg(feature = "bzip2")]
+        Compression::Bzip2 => Ok(Box::new(BufReader::new(
+            bzip2::bufread::MultiBzDecoder::new(reader),
+        ))),
+        #[cfg(feature = "xz")]
+        Compression::Xz => Ok(Box::new(BufReader::new(
+            xz2::bufread::XzDecoder::new_multi_decoder(reader),
+        ))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            let decoder = zstd::stream::read::Decoder::with_buffer(reader)
+                .map_err(|e| RamenskyError::io(path, e))?;
+            Ok(Box::new(BufReader::new(decoder)))
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(RamenskyError::InvalidConfiguration(format!(
+            "{} is compressed with {compression:?}, enable the {} feature to read it.",
+            path.display(),
+            compression.feature()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::safe_cracker::compression::Compression;
+
+    #[test]
+    fn can_detect_compression() {
+        assert_eq!(
+            Compression::detect(&[0x1f, 0x8b, 0x08]),
+            Some(Compression::Gzip)
+        );
+        assert_eq!(Compression::detect(b"BZh91AY"), Some(Compression::Bzip2));
+        assert_eq!(
+            Compression::detect(&[0xfd, b'7', b'z', b'X', b'Z', 0x00, 0x00]),
+            Some(Compression::Xz)
+        );
+        assert_eq!(
+            Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd]),
+            Some(Compression::Zstd)
+        );
+        assert_eq!(Compression::detect(b"test1\ntest2"), None);
+        assert_eq!(Compression::detect(b""), None);
+    }
+}
@@ -0,0 +1,505 @@
+//! Module containing the configuration files, that load and save [`Options`] as TOML or JSON, so a run
+//! can be checked in and repeated. Needs the `config` cargo feature.
+//! [`BruteForceOptions`] and [`CommonPwListOptions`] can be loaded and saved on their own the same way,
+//! e.g. to share a brute forcing setup between runs.
+//!
+//! ```rust,no_run
+//! use ramensky::safe_cracker::options::Options;
+//! use ramensky::safe_cracker::safe_cracker::SafeCracker;
+//!
+//! let options = Options::load("job.toml").unwrap();
+//! let safe_cracker = SafeCracker::build(options).unwrap();
+//! ```
+//!
+//! Values that are missing from the file use the defaults of [`Options::default()`], and durations
+//! are in seconds:
+//! ```toml
+//! try_common_passwords = false
+//! threads = 4
+//! checkpoint_path = "checkpoint.txt"
+//! checkpoint_interval = 30
+//!
+//! [brute_forcing_options]
+//! mask = "?u?l?l?l?d?d"
+//!
+//! [[custom_pw_lists]]
+//! path = "company.txt"
+//! priority = 1
+//! encoding = "latin1"
+//!
+//! [error_policy]
+//! policy = "retry"
+//! retries = 3
+//! backoff = 0.5
+//! ```
+//!
+//! The stages of the [pipeline](crate::safe_cracker::attack) are tables of the `pipeline` array, with the
+//! kind of attack as their `attack` key:
+//! ```toml
+//! [[pipeline]]
+//! attack = "wordlist"
+//! list = { path = "company.txt" }
+//!
+//! [[pipeline]]
+//! attack = "hybrid"
+//! list = { path = "company.txt" }
+//! mask = "?d?d?d?d"
+//! max_duration = 3600
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::RamenskyError;
+use crate::safe_cracker::brute_force_options::BruteForceOptions;
+use crate::safe_cracker::common_pw_list_options::CommonPwListOptions;
+use crate::safe_cracker::options::Options;
+
+/// File formats the options can be saved as, detected from the file extension.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+    fn of(path: &Path) -> Result<Self, RamenskyError> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Ok(Format::Toml),
+            Some("json") => Ok(Format::Json),
+            _ => Err(RamenskyError::InvalidConfiguration(format!(
+                "Configuration file {} needs to end with .toml or .json.",
+                path.display()
+            ))),
+        }
+    }
+}
+
+/// The options are validated while they are read, like the `build` functions do.
+impl Options {
+    /// Read the options from the configuration file at `path`, as TOML or JSON depending on its extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RamenskyError> {
+        load(path.as_ref(), Self::from_toml, Self::from_json)
+    }
+
+    /// Write the options to `path`, as TOML or JSON depending on its extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RamenskyError> {
+        save(self, path.as_ref())
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, RamenskyError> {
+        let options: Self = from_toml(toml)?;
+        options.validate()?;
+        Ok(options)
+    }
+
+    pub fn to_toml(&self) -> Result<String, RamenskyError> {
+        to_toml(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, RamenskyError> {
+        let options: Self = from_json(json)?;
+        options.validate()?;
+        Ok(options)
+    }
+
+    pub fn to_json(&self) -> Result<String, RamenskyError> {
+        to_json(self)
+    }
+}
+
+/// The options are validated while they are read, like [`build()`](BruteForceOptions::build) does.
+impl BruteForceOptions {
+    /// Read the options from the configuration file at `path`, as TOML or JSON depending on its extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RamenskyError> {
+        load(path.as_ref(), Self::from_toml, Self::from_json)
+    }
+
+    /// Write the options to `path`, as TOML or JSON depending on its extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RamenskyError> {
+        save(self, path.as_ref())
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, RamenskyError> {
+        from_toml(toml)
+    }
+
+    pub fn to_toml(&self) -> Result<String, RamenskyError> {
+        to_toml(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, RamenskyError> {
+        from_json(json)
+    }
+
+    pub fn to_json(&self) -> Result<String, RamenskyError> {
+        to_json(self)
+    }
+}
+
+/// The options are validated while they are read, like [`build()`](CommonPwListOptions::build) does.
+impl CommonPwListOptions {
+    /// Read the options from the configuration file at `path`, as TOML or JSON depending on its extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RamenskyError> {
+        load(path.as_ref(), Self::from_toml, Self::from_json)
+    }
+
+    /// Write the options to `path`, as TOML or JSON depending on its extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RamenskyError> {
+        save(self, path.as_ref())
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, RamenskyError> {
+        from_toml(toml)
+    }
+
+    pub fn to_toml(&self) -> Result<String, RamenskyError> {
+        to_toml(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, RamenskyError> {
+        from_json(json)
+    }
+
+    pub fn to_json(&self) -> Result<String, RamenskyError> {
+        to_json(self)
+    }
+}
+
+fn load<T>(
+    path: &Path,
+    from_toml: fn(&str) -> Result<T, RamenskyError>,
+    from_json: fn(&str) -> Result<T, RamenskyError>,
+) -> Result<T, RamenskyError> {
+    let format = Format::of(path)?;
+    let contents = fs::read_to_string(path).map_err(|e| RamenskyError::io(path, e))?;
+
+    match format {
+        Format::Toml => from_toml(&contents),
+        Format::Json => from_json(&contents),
+    }
+    .map_err(|e| RamenskyError::InvalidConfiguration(format!("{}: {e}", path.display())))
+}
+
+fn save(value: &impl Serialize, path: &Path) -> Result<(), RamenskyError> {
+    let contents = match Format::of(path)? {
+        Format::Toml => to_toml(value)?,
+        Format::Json => to_json(value)?,
+    };
+
+    fs::write(path, contents).map_err(|e| RamenskyError::io(path, e))
+}
+
+fn from_toml<T: DeserializeOwned>(toml: &str) -> Result<T, RamenskyError> {
+    toml::from_str(toml).map_err(|e| RamenskyError::InvalidConfiguration(e.to_string()))
+}
+
+fn to_toml(value: &impl Serialize) -> Result<String, RamenskyError> {
+    toml::to_string(value).map_err(|e| RamenskyError::InvalidConfiguration(e.to_string()))
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, RamenskyError> {
+    serde_json::from_str(json).map_err(|e| RamenskyError::InvalidConfiguration(e.to_string()))
+}
+
+fn to_json(value: &impl Serialize) -> Result<String, RamenskyError> {
+    serde_json::to_string_pretty(value)
+        .map_err(|e| RamenskyError::InvalidConfiguration(e.to_string()))
+}
+
+/// Durations in configuration files, in seconds, e.g. `1.5`.
+pub(crate) mod seconds {
+    use std::time::Duration;
+
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let seconds = f64::deserialize(deserializer)?;
+        Duration::try_from_secs_f64(seconds).map_err(D::Error::custom)
+    }
+}
+
+/// Optional durations in configuration files, in seconds.
+pub(crate) mod optional_seconds {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => super::seconds::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        #[derive(Deserialize)]
+        struct Seconds(#[serde(with = "super::seconds")] Duration);
+
+        Ok(Option::<Seconds>::deserialize(deserializer)?.map(|Seconds(duration)| duration))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+    use std::time::Duration;
+
+    use crate::safe_cracker::attack::Attack;
+    use crate::safe_cracker::brute_force_options::BruteForceOptions;
+    use crate::safe_cracker::common_pw_list_options::CommonPwListOptions;
+    use crate::safe_cracker::encoding_policy::EncodingPolicy;
+    use crate::safe_cracker::error_policy::ErrorPolicy;
+    use crate::safe_cracker::options::Options;
+    use crate::safe_cracker::reader_configuration::PwListConfiguration;
+
+    fn options() -> Options {
+        let brute_forcing_options = BruteForceOptions::build(1, 3, true, false, false)
+            .unwrap()
+            .with_position_charsets(&[Some("AB"), None, Some("!")])
+            .unwrap();
+
+        Options::builder()
+            .quiet(true)
+            .try_common_passwords(false)
+            .brute_forcing_options(brute_forcing_options)
+            .custom_pw_list(
+                PwListConfiguration::new("tests/resources/pw-list.txt", Some(10)).with_priority(1),
+            )
+            .custom_pw_list(
+                PwListConfiguration::embedded_common_passwords(None)
+                    .with_encoding(EncodingPolicy::Windows1252),
+            )
+            .threads(4)
+            .max_duration(Duration::from_millis(1_500))
+            .error_policy(ErrorPolicy::Requeue {
+                pause: Duration::from_secs(2),
+                max_requeues: 5,
+            })
+            .potfile_path("ramensky.pot")
+            .build()
+            .unwrap()
+    }
+
+    fn assert_same(options: &Options, expected: &Options) {
+        assert_eq!(options.quiet, expected.quiet);
+        assert_eq!(options.try_common_passwords, expected.try_common_passwords);
+        assert_eq!(options.custom_pw_lists, expected.custom_pw_lists);
+        assert_eq!(options.threads, expected.threads);
+        assert_eq!(options.max_duration, expected.max_duration);
+        assert_eq!(options.error_policy, expected.error_policy);
+        assert_eq!(options.potfile_path, expected.potfile_path);
+        assert_eq!(
+            options.brute_forcing_options.as_ref().unwrap().charsets(),
+            expected.brute_forcing_options.as_ref().unwrap().charsets()
+        );
+    }
+
+    #[test]
+    fn can_save_and_load() {
+        let options = options();
+
+        assert_same(
+            &Options::from_toml(&options.to_toml().unwrap()).unwrap(),
+            &options,
+        );
+        assert_same(
+            &Options::from_json(&options.to_json().unwrap()).unwrap(),
+            &options,
+        );
+
+        for extension in ["toml", "json"] {
+            let path = env::temp_dir().join(format!("ramensky-config-test.{extension}"));
+
+            options.save(&path).unwrap();
+            assert_same(&Options::load(&path).unwrap(), &options);
+            fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn can_save_and_load_brute_force_options() {
+        let options = BruteForceOptions::build_custom(1, 3, "abc")
+            .unwrap()
+            .with_position_charsets(&[Some("AB")])
+            .unwrap();
+
+        for saved in [
+            BruteForceOptions::from_toml(&options.to_toml().unwrap()).unwrap(),
+            BruteForceOptions::from_json(&options.to_json().unwrap()).unwrap(),
+        ] {
+            assert_eq!(saved.charsets(), options.charsets());
+        }
+
+        for extension in ["toml", "json"] {
+            let path = env::temp_dir().join(format!("ramensky-brute-force-test.{extension}"));
+
+            options.save(&path).unwrap();
+            assert_eq!(
+                BruteForceOptions::load(&path).unwrap().charsets(),
+                options.charsets()
+            );
+            fs::remove_file(&path).unwrap();
+        }
+
+        assert!(BruteForceOptions::from_toml("minimum_size = 5\nmaximum_size = 5").is_err());
+        assert!(BruteForceOptions::from_json(r#"{"mask": "?1"}"#).is_err());
+    }
+
+    #[test]
+    fn can_save_and_load_common_password_options() {
+        for amount_to_use in [Some(1_000), None] {
+            let options = CommonPwListOptions::build(amount_to_use).unwrap();
+
+            for saved in [
+                CommonPwListOptions::from_toml(&options.to_toml().unwrap()).unwrap(),
+                CommonPwListOptions::from_json(&options.to_json().unwrap()).unwrap(),
+            ] {
+                assert_eq!(saved.amount_to_use, amount_to_use);
+            }
+
+            for extension in ["toml", "json"] {
+                let path = env::temp_dir().join(format!("ramensky-common-test.{extension}"));
+
+                options.save(&path).unwrap();
+                assert_eq!(
+                    CommonPwListOptions::load(&path).unwrap().amount_to_use,
+                    amount_to_use
+                );
+                fs::remove_file(&path).unwrap();
+            }
+        }
+
+        assert!(CommonPwListOptions::from_toml("amount_to_use = 20000000").is_err());
+        assert!(CommonPwListOptions::from_json(r#"{"amount": 10}"#).is_err());
+    }
+
+    #[test]
+    fn can_load_partial_config() {
+        let options = Options::from_toml(
+            r#"
+            threads = 8
+
+            [brute_forcing_options]
+            mask = "?u?1"
+            mask_charsets = ["?d!"]
+
+            [[custom_pw_lists]]
+            path = "tests/resources/pw-list.txt"
+            encoding = "latin1"
+
+            [error_policy]
+            policy = "retry"
+            retries = 2
+            backoff = 0.5
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(options.threads, 8);
+        assert!(options.try_common_passwords);
+        assert_eq!(
+            options
+                .brute_forcing_options
+                .unwrap()
+                .keyspace_size()
+                .unwrap(),
+            26 * 11
+        );
+        assert_eq!(
+            options.custom_pw_lists,
+            [
+                PwListConfiguration::new("tests/resources/pw-list.txt", None)
+                    .with_encoding(EncodingPolicy::Latin1)
+            ]
+        );
+        assert_eq!(
+            options.error_policy,
+            ErrorPolicy::Retry {
+                retries: 2,
+                backoff: Duration::from_millis(500)
+            }
+        );
+    }
+
+    #[test]
+    fn can_load_pipeline() {
+        let options = Options::from_toml(
+            r#"
+            [[pipeline]]
+            attack = "rules"
+            rules_path = "tests/resources/rules.txt"
+            list = { path = "tests/resources/pw-list.txt" }
+
+            [[pipeline]]
+            attack = "mask"
+            mask = "?u?1"
+            mask_charsets = ["?d!"]
+            max_attempts = 1000
+            max_duration = 60
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(options.pipeline.len(), 2);
+        assert!(matches!(
+            &options.pipeline[0].attack,
+            Attack::Rules { list, .. }
+                if *list == PwListConfiguration::new("tests/resources/pw-list.txt", None)
+        ));
+        assert!(matches!(
+            &options.pipeline[1].attack,
+            Attack::Mask { mask, .. } if mask == "?u?1"
+        ));
+        assert_eq!(options.pipeline[1].max_attempts, Some(1000));
+        assert_eq!(
+            options.pipeline[1].max_duration,
+            Some(Duration::from_secs(60))
+        );
+
+        let saved = Options::from_toml(&options.to_toml().unwrap()).unwrap();
+        assert_eq!(
+            saved.pipeline[1].max_duration,
+            Some(Duration::from_secs(60))
+        );
+        assert!(Options::from_json(&options.to_json().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn should_validate() {
+        let invalid = [
+            "[brute_forcing_options]\nminimum_size = 5\nmaximum_size = 5",
+            "[brute_forcing_options]\nmask = \"?1\"",
+            "[brute_forcing_options]\ninclude_numbers = false\ninclude_characters = false",
+            "[common_password_options]\namount_to_use = 20000000",
+            "[[custom_pw_lists]]\npath = \"tests/resources/missing.txt\"",
+            "checkpoint_interval = -1",
+            "threads = 0",
+            "thread = 4",
+            "[[pipeline]]\nattack = \"mask\"\nmask = \"?1\"",
+            "[[pipeline]]\nattack = \"dictionary\"",
+        ];
+
+        for toml in invalid {
+            assert!(Options::from_toml(toml).is_err(), "{toml}");
+        }
+
+        assert!(Options::from_json(r#"{"error_policy": {"policy": "panic"}}"#).is_err());
+        assert!(Options::load(Path::new("config.yaml")).is_err());
+    }
+}
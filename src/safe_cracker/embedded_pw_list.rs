@@ -0,0 +1,33 @@
+//! Module containing the common password list, that is embedded in the library by the
+//! `embedded-common-passwords` feature.
+//!
+//! The list is compressed at build time from `resources/common-passwords.txt`. If the file is missing
+//! when building, or the feature is disabled, the list is not embedded, and `SafeCracker` reads it from
+//! [`common_pw_list_path`](crate::safe_cracker::options::Options::common_pw_list_path) instead.
+
+use std::io::BufRead;
+
+#[cfg(embedded_common_passwords)]
+const COMMON_PASSWORDS: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/common-passwords.txt.gz"));
+
+/// Whether the common password list is embedded in the library.
+pub fn is_available() -> bool {
+    cfg!(embedded_common_passwords)
+}
+
+/// Reader of the embedded common password list, or [`None`] if it is not embedded.
+#[cfg(embedded_common_passwords)]
+pub(crate) fn reader() -> Option<Box<dyn BufRead + Send>> {
+    use std::io::BufReader;
+
+    use flate2::read::GzDecoder;
+
+    Some(Box::new(BufReader::new(GzDecoder::new(COMMON_PASSWORDS))))
+}
+
+/// Reader of the embedded common password list, or [`None`] if it is not embedded.
+#[cfg(not(embedded_common_passwords))]
+pub(crate) fn reader() -> Option<Box<dyn BufRead + Send>> {
+    None
+}
@@ -0,0 +1,117 @@
+//! Module containing the policy that decides what happens to lines of a password list that are not valid UTF-8.
+
+/// What the `PasswordReader` does with a line that is not valid UTF-8, e.g. from a Latin-1 encoded
+/// password list. Lines that are valid UTF-8 are always read as they are.
+/// The amount of skipped and converted lines is counted in the
+/// [`Statistics`](crate::safe_cracker::statistics::Statistics) of the result.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum EncodingPolicy {
+    /// Stop reading, and return an [`Encoding`](crate::error::RamenskyError::Encoding) error.
+    #[default]
+    Error,
+
+    /// Skip the line, and move on to the next one.
+    Skip,
+
+    /// Replace the invalid bytes with `U+FFFD`, the replacement character.
+    Lossy,
+
+    /// Decode the line as Latin-1 (ISO-8859-1), where every byte is a character.
+    Latin1,
+
+    /// Decode the line as Windows-1252, the superset of Latin-1 used by Windows.
+    Windows1252,
+
+    /// Keep the bytes of the line as they are, for a [`ByteAdaptor`](crate::adaptor::byte_adaptor::ByteAdaptor).
+    /// Adaptors that take strings can not try these passwords, so they are skipped, and counted in
+    /// [`skipped_lines`](crate::safe_cracker::statistics::Statistics::skipped_lines).
+    Raw,
+}
+
+/// Result of decoding a line that is not valid UTF-8.
+pub(crate) enum Decoded {
+    Skipped,
+    Converted(Vec<u8>),
+    Raw(Vec<u8>),
+    Invalid,
+}
+
+/// Characters of the Windows-1252 bytes 0x80 to 0x9F. The bytes that are not defined are mapped to
+/// the control characters with the same code, like browsers do.
+const WINDOWS_1252: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+impl EncodingPolicy {
+    /// Decode a line that is not valid UTF-8.
+    pub(crate) fn decode(&self, line: Vec<u8>) -> Decoded {
+        match self {
+            EncodingPolicy::Error => Decoded::Invalid,
+            EncodingPolicy::Skip => Decoded::Skipped,
+            EncodingPolicy::Lossy => {
+                Decoded::Converted(String::from_utf8_lossy(&line).into_owned().into_bytes())
+            }
+            EncodingPolicy::Latin1 => Decoded::Converted(latin1(&line).into_bytes()),
+            EncodingPolicy::Windows1252 => Decoded::Converted(
+                line.iter()
+                    .map(|b| match b {
+                        0x80..=0x9F => WINDOWS_1252[(b - 0x80) as usize],
+                        _ => *b as char,
+                    })
+                    .collect::<String>()
+                    .into_bytes(),
+            ),
+            EncodingPolicy::Raw => Decoded::Raw(line),
+        }
+    }
+}
+
+/// Decode bytes as Latin-1, where every byte is the character with the same code.
+pub(crate) fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| *b as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::safe_cracker::encoding_policy::{Decoded, EncodingPolicy};
+
+    fn converted(policy: EncodingPolicy, line: &[u8]) -> String {
+        match policy.decode(line.to_vec()) {
+            Decoded::Converted(line) => String::from_utf8(line).unwrap(),
+            _ => panic!("{line:?} should have been converted."),
+        }
+    }
+
+    #[test]
+    fn can_decode() {
+        let line = b"caf\xe9 \x80";
+
+        assert_eq!(
+            converted(EncodingPolicy::Lossy, line),
+            "caf\u{FFFD} \u{FFFD}"
+        );
+        assert_eq!(converted(EncodingPolicy::Latin1, line), "café \u{80}");
+        assert_eq!(converted(EncodingPolicy::Windows1252, line), "café €");
+
+        assert!(matches!(
+            EncodingPolicy::Skip.decode(line.to_vec()),
+            Decoded::Skipped
+        ));
+        assert!(matches!(
+            EncodingPolicy::Error.decode(line.to_vec()),
+            Decoded::Invalid
+        ));
+        assert!(matches!(
+            EncodingPolicy::Raw.decode(line.to_vec()),
+            Decoded::Raw(raw) if raw == line
+        ));
+    }
+}
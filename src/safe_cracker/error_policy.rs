@@ -0,0 +1,45 @@
+//! Module containing the policy that decides what happens when the adaptor returns an error.
+
+use std::time::Duration;
+
+/// What `SafeCracker` does when [`try_password()`](crate::adaptor::base::BaseAdaptor::try_password)
+/// returns an error. Every error is counted in the [`Statistics`](crate::safe_cracker::statistics::Statistics)
+/// of the result, and sent to the observers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "policy", rename_all = "snake_case")
+)]
+pub enum ErrorPolicy {
+    /// Stop the run, and return the error. If checkpoints are enabled, the password is tried again
+    /// after resuming.
+    #[default]
+    Abort,
+
+    /// Treat the password as a failed attempt, and move on to the next one.
+    Skip,
+
+    /// Try the password again, up to `retries` times. The worker waits `backoff` before the first retry,
+    /// and twice as long before every following one. The run is stopped if the last retry fails as well.
+    Retry {
+        retries: u32,
+        #[cfg_attr(
+            feature = "config",
+            serde(with = "crate::safe_cracker::config::seconds")
+        )]
+        backoff: Duration,
+    },
+
+    /// Wait for `pause`, and put the password back in the queue, to be tried again after the passwords
+    /// that are already being tried. The run is stopped if the same password failed more than
+    /// `max_requeues` times.
+    Requeue {
+        #[cfg_attr(
+            feature = "config",
+            serde(with = "crate::safe_cracker::config::seconds")
+        )]
+        pause: Duration,
+        max_requeues: u32,
+    },
+}
@@ -0,0 +1,198 @@
+//! Module containing the masks that describe the shape of the password being brute forced.
+//!
+//! The syntax follows hashcat's mask syntax. Every `?x` placeholder is one position of the password,
+//! any other character is used literally:
+//!
+//! | Placeholder | Characters                                         |
+//! |-------------|----------------------------------------------------|
+//! | `?l`        | `abcdefghijklmnopqrstuvwxyz`                       |
+//! | `?u`        | `ABCDEFGHIJKLMNOPQRSTUVWXYZ`                       |
+//! | `?d`        | `0123456789`                                       |
+//! | `?h`        | `0123456789abcdef`                                 |
+//! | `?H`        | `0123456789ABCDEF`                                 |
+//! | `?s`        | `` !"#$%&'()*+,-./:;<=>?@[\]^_`{\|}~`` (with space) |
+//! | `?a`        | `?l?u?d?s`                                         |
+//! | `?1`..`?4`  | User defined charsets                              |
+//! | `??`        | `?`                                                |
+//!
+//! For example, `?u?l?l?l?l?l?d?d?d?d?s` will generate passwords like `Summer2024!`.
+
+use crate::error::RamenskyError;
+use crate::safe_cracker::brute_force_options::{
+    unique_chars, LOWERCASE, NUMBERS, SYMBOLS, UPPERCASE,
+};
+
+/// The maximum amount of user defined charsets (`?1`..`?4`).
+pub const MAX_CUSTOM_CHARSETS: usize = 4;
+
+#[derive(Clone, Debug)]
+pub struct Mask {
+    /// The mask this was built from, e.g. `?u?l?l?d`.
+    pub mask: String,
+
+    /// The user defined charsets the mask was built with, referenced in the mask as `?1`..`?4`.
+    pub custom_charsets: Vec<String>,
+
+    /// Characters to use, for every position of the password.
+    positions: Vec<Vec<char>>,
+}
+
+impl Mask {
+    /// Build a `Mask` from the provided mask.
+    /// `custom_charsets` are the user defined charsets, referenced in the mask as `?1`..`?4`.
+    /// They can contain built-in placeholders themselves, e.g. `?l?d_`.
+    pub fn build(mask: &str, custom_charsets: &[&str]) -> Result<Self, RamenskyError> {
+        if custom_charsets.len() > MAX_CUSTOM_CHARSETS {
+            return Err(RamenskyError::InvalidConfiguration(format!(
+                "At most {MAX_CUSTOM_CHARSETS} custom charsets can be provided, got {}.",
+                custom_charsets.len()
+            )));
+        }
+
+        let mut custom = vec![];
+
+        for charset in custom_charsets {
+            let positions = parse(charset, &[])?;
+            let chars = unique_chars(positions.concat());
+
+            if chars.is_empty() {
+                return Err(RamenskyError::InvalidConfiguration(
+                    "Custom charsets cannot be empty.".to_string(),
+                ));
+            }
+
+            custom.push(chars);
+        }
+
+        let positions = parse(mask, &custom)?;
+
+        if positions.is_empty() {
+            return Err(RamenskyError::InvalidConfiguration(
+                "Mask cannot be empty.".to_string(),
+            ));
+        }
+
+        Ok(Mask {
+            mask: mask.to_owned(),
+            custom_charsets: custom_charsets.iter().map(|c| c.to_string()).collect(),
+            positions,
+        })
+    }
+
+    /// Length of the passwords that are generated by this mask.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether the mask has no positions. Built masks are never empty.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Characters to use, for every position of the password.
+    pub fn positions(&self) -> &[Vec<char>] {
+        &self.positions
+    }
+}
+
+fn parse(mask: &str, custom: &[Vec<char>]) -> Result<Vec<Vec<char>>, RamenskyError> {
+    let mut positions = vec![];
+    let mut chars = mask.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '?' {
+            positions.push(vec![c]);
+            continue;
+        }
+
+        let placeholder = match chars.next() {
+            Some(p) => p,
+            None => {
+                return Err(RamenskyError::InvalidConfiguration(format!(
+                    "Mask {mask} cannot end with a single '?'."
+                )))
+            }
+        };
+
+        let position = match placeholder {
+            'l' => LOWERCASE.chars().collect(),
+            'u' => UPPERCASE.chars().collect(),
+            'd' => NUMBERS.chars().collect(),
+            'h' => "0123456789abcdef".chars().collect(),
+            'H' => "0123456789ABCDEF".chars().collect(),
+            's' => SYMBOLS.chars().collect(),
+            'a' => [LOWERCASE, UPPERCASE, NUMBERS, SYMBOLS]
+                .concat()
+                .chars()
+                .collect(),
+            '?' => vec!['?'],
+            '1'..='4' => {
+                let index = placeholder.to_digit(10).unwrap() as usize - 1;
+
+                match custom.get(index) {
+                    Some(charset) => charset.clone(),
+                    None => {
+                        return Err(RamenskyError::InvalidConfiguration(format!(
+                            "Mask {mask} uses ?{placeholder}, but custom charset {placeholder} is not provided."
+                        )))
+                    }
+                }
+            }
+            _ => {
+                return Err(RamenskyError::InvalidConfiguration(format!(
+                    "Mask {mask} contains unknown placeholder ?{placeholder}."
+                )))
+            }
+        };
+
+        positions.push(position);
+    }
+
+    Ok(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::safe_cracker::mask::Mask;
+
+    #[test]
+    fn can_parse() {
+        let mask = Mask::build("?u?l?d?s", &[]).unwrap();
+
+        assert_eq!(mask.len(), 4);
+        assert_eq!(mask.positions()[0].len(), 26);
+        assert_eq!(mask.positions()[1].len(), 26);
+        assert_eq!(mask.positions()[2].len(), 10);
+        assert_eq!(mask.positions()[3].len(), 33);
+        assert_eq!(Mask::build("?a", &[]).unwrap().positions()[0].len(), 95);
+    }
+
+    #[test]
+    fn can_parse_literals() {
+        let mask = Mask::build("ab??c", &[]).unwrap();
+
+        assert_eq!(
+            mask.positions(),
+            [vec!['a'], vec!['b'], vec!['?'], vec!['c']]
+        );
+    }
+
+    #[test]
+    fn can_parse_custom_charsets() {
+        let mask = Mask::build("?1?2", &["abca", "?d_"]).unwrap();
+
+        assert_eq!(mask.positions()[0], ['a', 'b', 'c']);
+        assert_eq!(mask.positions()[1].len(), 11);
+        assert_eq!(mask.positions()[1].last(), Some(&'_'));
+    }
+
+    #[test]
+    fn can_validate() {
+        assert!(Mask::build("", &[]).is_err());
+        assert!(Mask::build("?l?", &[]).is_err());
+        assert!(Mask::build("?x", &[]).is_err());
+        assert!(Mask::build("?1?2", &["abc"]).is_err());
+        assert!(Mask::build("?1", &[""]).is_err());
+        assert!(Mask::build("?1", &["a", "b", "c", "d", "e"]).is_err());
+    }
+}
@@ -1,10 +1,27 @@
 //! Module for the password cracker.
 
+pub mod attack;
 pub mod brute_force_options;
 pub mod brute_forcer; // Needs to be pub to be benchmarked.
+pub mod cancellation_token;
+mod candidates;
+pub mod checkpoint;
 pub mod common_pw_list_options;
+mod compression;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod embedded_pw_list;
+pub mod encoding_policy;
+pub mod error_policy;
+pub mod mask;
+pub mod observer;
 pub mod options;
+pub mod password;
 pub mod password_crack_result;
 pub mod password_reader; // Needs to be pub to be benchmarked.
+pub mod potfile;
 pub mod reader_configuration; // Needs to be pub to be benchmarked.
+pub mod rule;
+#[allow(clippy::module_inception)]
 pub mod safe_cracker;
+pub mod statistics;
@@ -0,0 +1,191 @@
+//! Module containing the observers, that get notified about the progress of `SafeCracker`.
+//!
+//! Observers can be used to render progress bars and ETAs, or to log the run somewhere else than stdout.
+//! Any closure that takes an [`Event`] can be used as an observer:
+//! ```rust
+//! use ramensky::safe_cracker::observer::Event;
+//! use ramensky::safe_cracker::options::Options;
+//! use ramensky::safe_cracker::safe_cracker::SafeCracker;
+//!
+//! let mut options = Options::default();
+//! options.quiet = true;
+//! options.try_common_passwords = false;
+//!
+//! let safe_cracker = SafeCracker::build(options)
+//!     .unwrap()
+//!     .with_observer(|event: &Event| {
+//!         if let Event::Progress(progress) = event {
+//!             println!("{} passwords tried, {:.0}/s", progress.tried, progress.attempts_per_second);
+//!         }
+//!     });
+//! ```
+
+use std::fmt::Display;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::RamenskyError;
+use crate::safe_cracker::attack::Attack;
+use crate::safe_cracker::password::Password;
+use crate::safe_cracker::statistics::Statistics;
+
+/// Snapshot of how far `SafeCracker` got.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    /// Amount of passwords tried so far.
+    pub tried: u64,
+
+    /// Average amount of passwords tried per second since the start.
+    pub attempts_per_second: f64,
+
+    /// Amount of passwords left to try, if known. The password lists are not counted upfront,
+    /// so this is only known once they are finished.
+    pub remaining: Option<u128>,
+
+    /// Time elapsed since the start.
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Estimated time until every password is tried, if the amount of remaining passwords is known.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.attempts_per_second <= 0.0 {
+            return None;
+        }
+
+        let seconds = self.remaining? as f64 / self.attempts_per_second;
+        Duration::try_from_secs_f64(seconds).ok()
+    }
+}
+
+/// Events sent to observers while `SafeCracker` is running.
+#[derive(Clone, Debug)]
+pub enum Event<'e> {
+    /// The run has started.
+    Started,
+
+    /// The stage at the given index of the pipeline has started, with its attack.
+    StageStarted(usize, &'e Attack),
+
+    /// A password is about to be tried. Passwords that are not valid UTF-8 are converted lossily.
+    Attempt(&'e str),
+
+    /// Sent every [`progress_interval`](crate::safe_cracker::options::Options::progress_interval).
+    Progress(Progress),
+
+    /// A checkpoint was saved to the given path.
+    CheckpointSaved(&'e Path),
+
+    /// The adaptor returned an error. Sent for every error, including the ones that are retried or skipped.
+    /// The [`ErrorPolicy`](crate::safe_cracker::error_policy::ErrorPolicy) of the
+    /// [`Options`](crate::safe_cracker::options::Options) decides whether the run stops.
+    AdaptorError(&'e RamenskyError),
+
+    /// The password was found. Contains the password, and the statistics of the execution.
+    Success(&'e Password, &'e Statistics),
+
+    /// Every password was tried, without finding the password. Contains the statistics of the execution.
+    Failure(&'e Statistics),
+
+    /// The run was stopped before every password was tried. Contains the statistics of the execution.
+    Interrupted(&'e Statistics),
+}
+
+/// Observers get notified about every [`Event`] of a `SafeCracker` run.
+/// As passwords are tried by multiple workers, events can be sent from any of them.
+pub trait Observer: Send + Sync {
+    fn on_event(&self, event: &Event);
+}
+
+impl<F: Fn(&Event) + Send + Sync> Observer for F {
+    fn on_event(&self, event: &Event) {
+        self(event)
+    }
+}
+
+/// Observer that prints every event to stdout, with a timestamp.
+/// Used by `SafeCracker` unless [`quiet`](crate::safe_cracker::options::Options::quiet) is true.
+pub struct PrintObserver;
+
+impl PrintObserver {
+    fn print(message: impl Display) {
+        println!("{:?}\t{message}", chrono::offset::Local::now());
+    }
+
+    fn print_statistics(statistics: &Statistics) {
+        if statistics.errors > 0 {
+            Self::print(format!(
+                "The adaptor returned {} errors.",
+                statistics.errors
+            ));
+        }
+
+        Self::print(format!(
+            "Execution took {} seconds.",
+            statistics.elapsed.as_secs()
+        ));
+    }
+}
+
+impl Observer for PrintObserver {
+    fn on_event(&self, event: &Event) {
+        match event {
+            Event::Started => Self::print("Starting attempt"),
+            Event::StageStarted(index, attack) => {
+                Self::print(format!("Starting stage {index} ({attack})."))
+            }
+            Event::Attempt(pw) => Self::print(format!("Trying password {pw}")),
+            Event::Progress(progress) => {
+                let eta = match progress.eta() {
+                    Some(eta) => format!(", {} seconds remaining", eta.as_secs()),
+                    None => String::new(),
+                };
+
+                Self::print(format!(
+                    "Tried {} passwords, {:.0} passwords per second{eta}.",
+                    progress.tried, progress.attempts_per_second
+                ))
+            }
+            Event::CheckpointSaved(path) => {
+                Self::print(format!("Saved checkpoint to {}.", path.display()))
+            }
+            Event::AdaptorError(e) => Self::print(e),
+            Event::Success(pw, statistics) => {
+                Self::print(format!("Success! {pw} is the password."));
+                Self::print_statistics(statistics);
+            }
+            Event::Failure(statistics) => {
+                Self::print("Failure! Could not find the password.");
+                Self::print_statistics(statistics);
+            }
+            Event::Interrupted(statistics) => {
+                Self::print(format!(
+                    "Interrupted after trying {} passwords.",
+                    statistics.attempts
+                ));
+                Self::print_statistics(statistics);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::safe_cracker::observer::Progress;
+
+    #[test]
+    fn can_estimate_remaining_time() {
+        let mut progress = Progress {
+            tried: 100,
+            attempts_per_second: 50.0,
+            remaining: Some(1_000),
+            elapsed: Duration::from_secs(2),
+        };
+        assert_eq!(progress.eta(), Some(Duration::from_secs(20)));
+
+        progress.remaining = None;
+        assert_eq!(progress.eta(), None);
+    }
+}
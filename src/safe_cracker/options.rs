@@ -13,14 +13,48 @@
 //! use ramensky::safe_cracker::common_pw_list_options::CommonPwListOptions;
 //! use ramensky::safe_cracker::options::Options;
 //! use ramensky::safe_cracker::safe_cracker::SafeCracker;
-//! let safe_cracker = SafeCracker::build(Options::new(true, true, Some(CommonPwListOptions::default()), true, Some(BruteForceOptions::default()), None)).unwrap();
+//! let safe_cracker = SafeCracker::build(Options::new(true, true, Some(CommonPwListOptions::default()), true, Some(BruteForceOptions::default()), vec![])).unwrap();
+//! ```
+//!
+//! Multiple worker threads:
+//! ```rust
+//! use ramensky::safe_cracker::options::Options;
+//! use ramensky::safe_cracker::safe_cracker::SafeCracker;
+//! let mut options = Options::default();
+//! options.threads = 8;
+//! let safe_cracker = SafeCracker::build(options).unwrap();
+//! ```
+//!
+//! With named setters, see [`OptionsBuilder`]:
+//! ```rust
+//! use ramensky::safe_cracker::options::Options;
+//! let options = Options::builder()
+//!     .threads(8)
+//!     .checkpoint_path("checkpoint.txt")
+//!     .build()
+//!     .unwrap();
+//! ```
+
+use std::path::PathBuf;
+use std::time::Duration;
 
+use crate::error::RamenskyError;
+use crate::safe_cracker::attack::AttackStage;
 use crate::safe_cracker::brute_force_options::BruteForceOptions;
 use crate::safe_cracker::common_pw_list_options::CommonPwListOptions;
+use crate::safe_cracker::error_policy::ErrorPolicy;
+use crate::safe_cracker::reader_configuration::PwListConfiguration;
 
-pub struct Options<'a> {
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct Options {
     /// If `quiet` is true, no messages will be printed, like passwords being generated, elapsed time, or the password (if found).
     /// If disabled, make sure you are consuming the resulting [`PasswordCrackResult`](crate::safe_cracker::password_crack_result::PasswordCrackResult) correctly.
+    /// The messages are printed by a [`PrintObserver`](crate::safe_cracker::observer::PrintObserver).
     pub quiet: bool,
 
     /// Whether to try the most common 10M passwords. This will be the first thing that runs.
@@ -29,44 +63,381 @@ pub struct Options<'a> {
     /// Brute forcing options. Will only be used if ```try_brute_forcing``` is true.
     pub common_password_options: Option<CommonPwListOptions>,
 
+    /// Path to a common password list, to use instead of the one embedded in the library.
+    /// If not provided and the list is not embedded (see [`embedded_pw_list`](crate::safe_cracker::embedded_pw_list)),
+    /// `resources/common-passwords.txt` is read, relative to the working directory.
+    pub common_pw_list_path: Option<PathBuf>,
+
     /// Whether to try to brute force the password. This will run last.
     pub try_brute_forcing: bool,
 
     /// Brute forcing options. Will only be used if ```try_brute_forcing``` is true.
     pub brute_forcing_options: Option<BruteForceOptions>,
 
-    /// Path to a custom passwords list. If provided, this will run first.
-    /// The password list file needs to be newline separated list of passwords.
-    pub custom_pw_list_path: Option<&'a str>,
+    /// Custom password lists, tried after the common passwords. The password list files need to be
+    /// newline separated lists of passwords. Every list can limit and skip its passwords, and lists
+    /// with a higher [`priority`](PwListConfiguration::priority) are tried first.
+    /// The common password list has a priority of 0, so custom lists with a higher priority are tried before it.
+    pub custom_pw_lists: Vec<PwListConfiguration>,
+
+    /// Path to a rule file, to mangle every password from the password lists, e.g. `password` into `Password1!`.
+    /// The rule file needs to be a newline separated list of rules, see [`Rule`](crate::safe_cracker::rule::Rule)
+    /// for the syntax. Brute forcing is not affected by the rules.
+    pub rules_path: Option<PathBuf>,
+
+    /// Stages to run in order, each with its own budget, see [`attack`](crate::safe_cracker::attack).
+    /// If provided, the stages replace the common password list, the custom password lists, the rules and
+    /// the brute forcing, which are run in that order otherwise.
+    pub pipeline: Vec<AttackStage>,
+
+    /// Amount of worker threads that try passwords in parallel. Defaults to 1.
+    /// Increasing this is only useful if the adaptor is slow, e.g. spawns a process for every attempt.
+    pub threads: usize,
+
+    /// Amount of passwords that every worker hands to the adaptor at once, with
+    /// [`try_passwords()`](crate::adaptor::base::BaseAdaptor::try_passwords). Defaults to 1.
+    /// Increasing this is only useful if the adaptor can check many passwords more cheaply together.
+    pub batch_size: usize,
+
+    /// If provided, a [`Checkpoint`](crate::safe_cracker::checkpoint::Checkpoint) will be saved to this
+    /// path periodically, so the run can be resumed with
+    /// [`SafeCracker::resume()`](crate::safe_cracker::safe_cracker::SafeCracker::resume) if the process dies.
+    pub checkpoint_path: Option<PathBuf>,
+
+    /// How often the checkpoint is saved, if `checkpoint_path` is provided. Defaults to 1 minute.
+    #[cfg_attr(
+        feature = "config",
+        serde(with = "crate::safe_cracker::config::seconds")
+    )]
+    pub checkpoint_interval: Duration,
+
+    /// How often a [`Progress`](crate::safe_cracker::observer::Progress) event is sent to the observers.
+    /// Defaults to 1 second.
+    #[cfg_attr(
+        feature = "config",
+        serde(with = "crate::safe_cracker::config::seconds")
+    )]
+    pub progress_interval: Duration,
+
+    /// If provided, the run is interrupted once it has been running for this long.
+    #[cfg_attr(
+        feature = "config",
+        serde(
+            with = "crate::safe_cracker::config::optional_seconds",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub max_duration: Option<Duration>,
+
+    /// If provided, the run is interrupted once this many passwords have been tried.
+    pub max_attempts: Option<u64>,
+
+    /// What to do when the adaptor returns an error. Defaults to [`ErrorPolicy::Abort`].
+    pub error_policy: ErrorPolicy,
+
+    /// If provided, found passwords are appended to the [`Potfile`](crate::safe_cracker::potfile::Potfile)
+    /// at this path, and targets that are already in it return their password right away.
+    /// Only used if the adaptor has a [`fingerprint`](crate::adaptor::base::BaseAdaptor::fingerprint).
+    pub potfile_path: Option<PathBuf>,
 }
 
-impl<'a> Options<'a> {
+impl Options {
     pub fn new(
         quiet: bool,
         try_common_passwords: bool,
         common_password_options: Option<CommonPwListOptions>,
         try_brute_forcing: bool,
         brute_forcing_options: Option<BruteForceOptions>,
-        custom_pw_list_path: Option<&'a str>,
+        custom_pw_lists: Vec<PwListConfiguration>,
     ) -> Self {
         Options {
             quiet,
             try_common_passwords,
             common_password_options,
+            common_pw_list_path: None,
             try_brute_forcing,
             brute_forcing_options,
-            custom_pw_list_path,
+            custom_pw_lists,
+            rules_path: None,
+            pipeline: vec![],
+            threads: 1,
+            batch_size: 1,
+            checkpoint_path: None,
+            checkpoint_interval: Duration::from_secs(60),
+            progress_interval: Duration::from_secs(1),
+            max_duration: None,
+            max_attempts: None,
+            error_policy: ErrorPolicy::Abort,
+            potfile_path: None,
         }
     }
 
-    pub fn default() -> Self {
+    /// Start building options from the defaults, see [`OptionsBuilder`].
+    pub fn builder() -> OptionsBuilder {
+        OptionsBuilder::default()
+    }
+
+    /// Check that the options can be used to build a `SafeCracker`. Called by
+    /// [`OptionsBuilder::build()`] and [`SafeCracker::build()`](crate::safe_cracker::safe_cracker::SafeCracker::build).
+    pub fn validate(&self) -> Result<(), RamenskyError> {
+        if self.try_common_passwords && self.common_password_options.is_none() {
+            return Err(RamenskyError::InvalidConfiguration(
+                "common_password_options needs to set if try_common_passwords is enabled."
+                    .to_string(),
+            ));
+        }
+
+        for custom_pw_list in &self.custom_pw_lists {
+            custom_pw_list.validate()?;
+        }
+
+        for stage in &self.pipeline {
+            stage.attack.validate()?;
+        }
+
+        if self.threads == 0 {
+            return Err(RamenskyError::InvalidConfiguration(
+                "threads needs to be at least 1.".to_string(),
+            ));
+        }
+
+        if self.batch_size == 0 {
+            return Err(RamenskyError::InvalidConfiguration(
+                "batch_size needs to be at least 1.".to_string(),
+            ));
+        }
+
+        if self.try_brute_forcing && self.brute_forcing_options.is_none() {
+            return Err(RamenskyError::InvalidConfiguration(
+                "brute_forcing_options needs to set if try_brute_forcing is enabled.".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
         Self::new(
             false,
             true,
             Some(CommonPwListOptions::default()),
             true,
             Some(BruteForceOptions::default()),
-            None,
+            vec![],
         )
     }
 }
+
+/// `OptionsBuilder` builds [`Options`] with named setters, starting from [`Options::default()`].
+/// The options are validated once they are built.
+///
+/// ```rust,no_run
+/// use ramensky::safe_cracker::options::Options;
+/// use ramensky::safe_cracker::reader_configuration::PwListConfiguration;
+///
+/// let options = Options::builder()
+///     .try_common_passwords(false)
+///     .custom_pw_list(PwListConfiguration::new("words.txt", None))
+///     .rules_path("rules.txt")
+///     .threads(8)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Default)]
+pub struct OptionsBuilder {
+    options: Options,
+}
+
+/// Continue building from existing options, e.g. to override the values of a configuration file.
+impl From<Options> for OptionsBuilder {
+    fn from(options: Options) -> Self {
+        OptionsBuilder { options }
+    }
+}
+
+impl OptionsBuilder {
+    /// See [`Options::quiet`].
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.options.quiet = quiet;
+        self
+    }
+
+    /// See [`Options::try_common_passwords`].
+    pub fn try_common_passwords(mut self, try_common_passwords: bool) -> Self {
+        self.options.try_common_passwords = try_common_passwords;
+        self
+    }
+
+    /// See [`Options::common_password_options`].
+    pub fn common_password_options(mut self, common_password_options: CommonPwListOptions) -> Self {
+        self.options.common_password_options = Some(common_password_options);
+        self
+    }
+
+    /// See [`Options::common_pw_list_path`].
+    pub fn common_pw_list_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.common_pw_list_path = Some(path.into());
+        self
+    }
+
+    /// See [`Options::try_brute_forcing`].
+    pub fn try_brute_forcing(mut self, try_brute_forcing: bool) -> Self {
+        self.options.try_brute_forcing = try_brute_forcing;
+        self
+    }
+
+    /// See [`Options::brute_forcing_options`].
+    pub fn brute_forcing_options(mut self, brute_forcing_options: BruteForceOptions) -> Self {
+        self.options.brute_forcing_options = Some(brute_forcing_options);
+        self
+    }
+
+    /// Add a custom password list, see [`Options::custom_pw_lists`]. Can be called multiple times.
+    pub fn custom_pw_list(mut self, custom_pw_list: PwListConfiguration) -> Self {
+        self.options.custom_pw_lists.push(custom_pw_list);
+        self
+    }
+
+    /// See [`Options::rules_path`].
+    pub fn rules_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.rules_path = Some(path.into());
+        self
+    }
+
+    /// Add a stage to the [`pipeline`](Options::pipeline), after the ones that were already added.
+    pub fn stage(mut self, stage: AttackStage) -> Self {
+        self.options.pipeline.push(stage);
+        self
+    }
+
+    /// See [`Options::threads`].
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.options.threads = threads;
+        self
+    }
+
+    /// See [`Options::batch_size`].
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.options.batch_size = batch_size;
+        self
+    }
+
+    /// See [`Options::checkpoint_path`].
+    pub fn checkpoint_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.checkpoint_path = Some(path.into());
+        self
+    }
+
+    /// See [`Options::checkpoint_interval`].
+    pub fn checkpoint_interval(mut self, checkpoint_interval: Duration) -> Self {
+        self.options.checkpoint_interval = checkpoint_interval;
+        self
+    }
+
+    /// See [`Options::progress_interval`].
+    pub fn progress_interval(mut self, progress_interval: Duration) -> Self {
+        self.options.progress_interval = progress_interval;
+        self
+    }
+
+    /// See [`Options::max_duration`].
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.options.max_duration = Some(max_duration);
+        self
+    }
+
+    /// See [`Options::max_attempts`].
+    pub fn max_attempts(mut self, max_attempts: u64) -> Self {
+        self.options.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// See [`Options::error_policy`].
+    pub fn error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.options.error_policy = error_policy;
+        self
+    }
+
+    /// See [`Options::potfile_path`].
+    pub fn potfile_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.potfile_path = Some(path.into());
+        self
+    }
+
+    /// Validate the options, see [`Options::validate()`].
+    pub fn build(self) -> Result<Options, RamenskyError> {
+        self.options.validate()?;
+        Ok(self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::Duration;
+
+    use crate::safe_cracker::brute_force_options::BruteForceOptions;
+    use crate::safe_cracker::error_policy::ErrorPolicy;
+    use crate::safe_cracker::options::Options;
+    use crate::safe_cracker::reader_configuration::PwListConfiguration;
+
+    #[test]
+    fn can_build() {
+        let options = Options::builder()
+            .quiet(true)
+            .try_common_passwords(false)
+            .brute_forcing_options(BruteForceOptions::build_mask("?d?d", &[]).unwrap())
+            .custom_pw_list(PwListConfiguration::new(
+                "tests/resources/pw-list.txt",
+                None,
+            ))
+            .custom_pw_list(PwListConfiguration::new(
+                "tests/resources/pw-list-2.txt",
+                None,
+            ))
+            .threads(4)
+            .checkpoint_path("checkpoint.txt")
+            .max_duration(Duration::from_secs(10))
+            .error_policy(ErrorPolicy::Skip)
+            .build()
+            .unwrap();
+
+        assert!(options.quiet);
+        assert!(!options.try_common_passwords);
+        assert!(options.try_brute_forcing);
+        assert_eq!(options.custom_pw_lists.len(), 2);
+        assert_eq!(options.threads, 4);
+        assert_eq!(
+            options.checkpoint_path.as_deref(),
+            Some(Path::new("checkpoint.txt"))
+        );
+        assert_eq!(options.max_duration, Some(Duration::from_secs(10)));
+        assert_eq!(options.max_attempts, None);
+        assert_eq!(options.error_policy, ErrorPolicy::Skip);
+    }
+
+    #[test]
+    fn should_validate() {
+        assert!(Options::builder().build().is_ok());
+        assert!(Options::builder().threads(0).build().is_err());
+        assert!(Options::builder().batch_size(0).build().is_err());
+        assert!(Options::builder()
+            .custom_pw_list(PwListConfiguration::new(
+                "tests/resources/missing.txt",
+                None
+            ))
+            .build()
+            .is_err());
+
+        let mut options = Options {
+            brute_forcing_options: None,
+            ..Options::default()
+        };
+        assert!(options.validate().is_err());
+
+        options.try_brute_forcing = false;
+        assert!(options.validate().is_ok());
+    }
+}
@@ -0,0 +1,78 @@
+//! Module containing the password that was found by `SafeCracker`.
+
+use std::fmt::{Display, Formatter};
+
+use crate::hex;
+
+/// A password, as the bytes that were passed to the adaptor.
+/// Passwords are valid UTF-8 unless they were read with
+/// [`EncodingPolicy::Raw`](crate::safe_cracker::encoding_policy::EncodingPolicy::Raw).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Password(Vec<u8>);
+
+impl Password {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Password(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// The password as a string, or [`None`] if it is not valid UTF-8.
+    pub fn to_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.0).ok()
+    }
+}
+
+/// Passwords that are not valid UTF-8 are displayed as `$HEX[...]`, like hashcat does.
+impl Display for Password {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.to_str() {
+            Some(password) => write!(f, "{password}"),
+            None => write!(f, "$HEX[{}]", hex::encode(&self.0)),
+        }
+    }
+}
+
+impl From<String> for Password {
+    fn from(password: String) -> Self {
+        Password(password.into_bytes())
+    }
+}
+
+impl From<&str> for Password {
+    fn from(password: &str) -> Self {
+        Password(password.as_bytes().to_vec())
+    }
+}
+
+impl PartialEq<str> for Password {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for Password {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::safe_cracker::password::Password;
+
+    #[test]
+    fn can_display() {
+        assert_eq!(Password::from("café").to_string(), "café");
+        assert_eq!(
+            Password::new(b"caf\xe9".to_vec()).to_string(),
+            "$HEX[636166e9]"
+        );
+    }
+}
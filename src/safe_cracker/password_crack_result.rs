@@ -1,10 +1,18 @@
-use std::time::Duration;
+use crate::safe_cracker::checkpoint::Checkpoint;
+use crate::safe_cracker::password::Password;
+use crate::safe_cracker::statistics::Statistics;
 
 /// Result of the execution of the `SafeCracker`.
 pub enum PasswordCrackResult {
-    /// Password was found successfully. Contains the password, and the duration of the execution.
-    Success(String, Duration),
+    /// Password was found successfully. Contains the password, and the statistics of the execution.
+    Success(Password, Statistics),
 
-    /// Password was not found. Contains the duration of the execution.
-    Failure(Duration),
+    /// Password was not found. Contains the statistics of the execution.
+    Failure(Statistics),
+
+    /// The execution was stopped before every password was tried, either because it was cancelled,
+    /// or [`max_duration`](crate::safe_cracker::options::Options::max_duration) or
+    /// [`max_attempts`](crate::safe_cracker::options::Options::max_attempts) was reached.
+    /// Contains the checkpoint to resume from, and the statistics of the execution.
+    Interrupted(Checkpoint, Statistics),
 }
@@ -1,82 +1,270 @@
 //! Module containing the iterator that reads from provided password lists.
 
-use std::error::Error;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Lines};
+use std::cmp::Reverse;
+use std::io::{self, BufRead};
+use std::mem;
 
-use crate::safe_cracker::reader_configuration::PwListConfiguration;
+use crate::error::RamenskyError;
+use crate::safe_cracker::compression;
+use crate::safe_cracker::embedded_pw_list;
+use crate::safe_cracker::encoding_policy::{Decoded, EncodingPolicy};
+use crate::safe_cracker::reader_configuration::{PwListConfiguration, PwListSource};
 
 /// `PasswordReader` is an iterator that spits out a password everytime [`next()`](PasswordReader::next) is called.
 /// These passwords are read from password files, provided in the [`build()`](PasswordReader::build) method.
+/// Passwords are returned as bytes, which are valid UTF-8 unless the [`EncodingPolicy`] of the password file
+/// is [`Raw`](EncodingPolicy::Raw).
 pub struct PasswordReader {
-    lines: Vec<Lines<BufReader<File>>>,
+    paths: Vec<String>,
+    lines: Vec<Box<dyn BufRead + Send>>,
     limits: Vec<Option<usize>>,
+    encodings: Vec<EncodingPolicy>,
+    /// Amount of lines that were not valid UTF-8, and were skipped or converted.
+    skipped_lines: u64,
+    converted_lines: u64,
     lines_index: usize,
-    limit_acc: usize,
+    /// Amount of lines read from every password file.
+    line_offsets: Vec<usize>,
+    /// The error that stopped the reading, if any.
+    error: Option<RamenskyError>,
 }
 
 impl PasswordReader {
     /// Build a `PasswordReader` from the provided password file paths.
-    /// Password files need to be newline separated lists of strings.
-    pub fn build(pw_list_configs: Vec<PwListConfiguration>) -> Result<Self, Box<dyn Error>> {
+    /// Password files need to be newline separated lists of strings. They can be compressed with gzip, bzip2,
+    /// xz or zstd, if the cargo feature of the compression is enabled.
+    /// They are read by [`priority`](PwListConfiguration::priority), and in the order they were provided
+    /// if the priorities are the same.
+    pub fn build(mut pw_list_configs: Vec<PwListConfiguration>) -> Result<Self, RamenskyError> {
+        let mut paths = vec![];
         let mut lines = vec![];
         let mut limits = vec![];
+        let mut encodings = vec![];
+        let mut line_offsets = vec![];
+        let mut skips = vec![];
+
+        // The sort is stable, so lists with the same priority keep their order.
+        pw_list_configs.sort_by_key(|config| Reverse(config.priority));
 
         for config in pw_list_configs {
-            let file = File::open(config.path)?;
-            let reader = BufReader::new(file);
-            lines.push(reader.lines());
-            limits.push(config.amount);
+            let reader = Self::open(&config.source)?;
+            paths.push(config.source.to_string());
+            lines.push(reader);
+            // Skipped lines count towards the line offsets, so the limit does too.
+            limits.push(config.amount.map(|amount| amount + config.skip));
+            encodings.push(config.encoding);
+            line_offsets.push(0);
+            skips.push(config.skip);
         }
 
-        Ok(Self {
+        let mut password_reader = Self {
+            paths,
             lines,
             lines_index: 0,
             limits,
-            limit_acc: 0,
-        })
-    }
-}
+            encodings,
+            skipped_lines: 0,
+            converted_lines: 0,
+            line_offsets,
+            error: None,
+        };
+        password_reader.skip_lines(&skips)?;
 
-impl Iterator for PasswordReader {
-    type Item = String;
+        Ok(password_reader)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.lines_index >= self.lines.len() {
-            // We have exhausted all password sources.
-            return None;
+    fn open(source: &PwListSource) -> Result<Box<dyn BufRead + Send>, RamenskyError> {
+        match source {
+            PwListSource::File(path) => compression::open(path),
+            PwListSource::EmbeddedCommonPasswords => embedded_pw_list::reader().ok_or_else(|| {
+                RamenskyError::InvalidConfiguration(
+                    "The common password list is not embedded, enable the embedded-common-passwords feature or provide its path."
+                        .to_string(),
+                )
+            }),
         }
-        let current_limit = self.limits[self.lines_index];
+    }
+
+    /// Paths of the password files, in the order they are read.
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
 
-        if current_limit.is_some() && self.limit_acc >= current_limit.unwrap() {
-            // Move on to the next buffer.
-            self.lines_index += 1;
-            self.limit_acc = 0;
-            return self.next();
+    /// Amount of lines read or skipped so far from every password file, in the order they are read.
+    pub fn line_offsets(&self) -> &[usize] {
+        &self.line_offsets
+    }
+
+    /// Index of the password file that is currently being read, or [`None`] if all of them are exhausted.
+    pub fn current_source(&self) -> Option<usize> {
+        if self.lines_index < self.lines.len() {
+            Some(self.lines_index)
         } else {
-            let current_lines = &mut self.lines[self.lines_index];
+            None
+        }
+    }
+
+    /// The error that stopped the reading, e.g. a line that is not valid UTF-8.
+    /// Once an error occurs, no more passwords are returned.
+    pub fn take_error(&mut self) -> Option<RamenskyError> {
+        self.error.take()
+    }
+
+    /// Amount of lines that were skipped, because they were not valid UTF-8.
+    pub fn skipped_lines(&self) -> u64 {
+        self.skipped_lines
+    }
+
+    /// Amount of lines that were converted to UTF-8, because they were not valid UTF-8.
+    pub fn converted_lines(&self) -> u64 {
+        self.converted_lines
+    }
+
+    /// Whether the reading was stopped by an error.
+    pub fn has_error(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Skip lines from every password file, until the given line offsets are reached, e.g. to continue
+    /// from the [`line_offsets()`](PasswordReader::line_offsets) of a previous run.
+    /// Lines that were already read are not read again.
+    pub fn skip_lines(&mut self, line_offsets: &[usize]) -> Result<(), RamenskyError> {
+        if line_offsets.len() != self.lines.len() {
+            return Err(RamenskyError::InvalidConfiguration(format!(
+                "Expected line offsets for {} password files, got {}.",
+                self.lines.len(),
+                line_offsets.len()
+            )));
+        }
+
+        let mut first_unfinished = None;
+        let mut line = vec![];
+
+        for (index, offset) in line_offsets.iter().enumerate() {
+            let mut exhausted = false;
+
+            while self.line_offsets[index] < *offset {
+                match read_line(&mut self.lines[index], &mut line) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        exhausted = true;
+                        break;
+                    }
+                    Err(e) => return Err(RamenskyError::io(&self.paths[index], e)),
+                }
+                self.line_offsets[index] += 1;
+            }
+
+            let limit_reached =
+                self.limits[index].is_some_and(|limit| self.line_offsets[index] >= limit);
+
+            if first_unfinished.is_none() && !exhausted && !limit_reached {
+                first_unfinished = Some(index);
+            }
+        }
+
+        // Continue from the first password file that still has lines to read.
+        self.lines_index = first_unfinished.unwrap_or(self.lines.len());
 
-            match current_lines.next() {
-                None => {
+        Ok(())
+    }
+
+    /// Read the next password into `buffer`, replacing its contents, so the same buffer can be reused for
+    /// every password. Returns false once every password file is exhausted, or an error occurred.
+    pub fn next_into(&mut self, buffer: &mut Vec<u8>) -> bool {
+        while self.lines_index < self.lines.len() && self.error.is_none() {
+            let index = self.lines_index;
+
+            if self.limits[index].is_some_and(|limit| self.line_offsets[index] >= limit) {
+                // Move on to the next buffer.
+                self.lines_index += 1;
+                continue;
+            }
+
+            match read_line(&mut self.lines[index], buffer) {
+                Ok(true) => {}
+                Ok(false) => {
                     // Move on to the next buffer.
                     self.lines_index += 1;
-                    self.limit_acc = 0;
-                    self.next()
+                    continue;
+                }
+                Err(e) => {
+                    self.error = Some(RamenskyError::io(&self.paths[index], e));
+                    return false;
+                }
+            }
+            self.line_offsets[index] += 1;
+
+            if std::str::from_utf8(buffer).is_ok() {
+                return true;
+            }
+
+            match self.encodings[index].decode(mem::take(buffer)) {
+                Decoded::Converted(line) => {
+                    self.converted_lines += 1;
+                    *buffer = line;
+                    return true;
+                }
+                Decoded::Raw(line) => {
+                    *buffer = line;
+                    return true;
                 }
-                Some(res) => {
-                    self.limit_acc += 1;
-                    Some(res.unwrap())
+                Decoded::Skipped => self.skipped_lines += 1,
+                Decoded::Invalid => {
+                    self.error = Some(RamenskyError::Encoding {
+                        path: self.paths[index].clone(),
+                        line: self.line_offsets[index],
+                    });
                 }
             }
         }
+
+        // We have exhausted all password sources, or could not read one of them.
+        false
+    }
+}
+
+/// Read the next line into `line`, without the line ending. Returns false if the end of the file is reached.
+fn read_line(reader: &mut Box<dyn BufRead + Send>, line: &mut Vec<u8>) -> io::Result<bool> {
+    line.clear();
+
+    if reader.read_until(b'\n', line)? == 0 {
+        return Ok(false);
+    }
+
+    // Same line endings as `BufRead::lines()`.
+    if line.ends_with(b"\n") {
+        line.pop();
+
+        if line.ends_with(b"\r") {
+            line.pop();
+        }
+    }
+
+    Ok(true)
+}
+
+impl Iterator for PasswordReader {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut password = vec![];
+        self.next_into(&mut password).then_some(password)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::error::RamenskyError;
+    use crate::safe_cracker::embedded_pw_list;
+    use crate::safe_cracker::encoding_policy::EncodingPolicy;
     use crate::safe_cracker::password_reader::PasswordReader;
     use crate::safe_cracker::reader_configuration::PwListConfiguration;
 
+    fn strings(pws: impl Iterator<Item = Vec<u8>>) -> Vec<String> {
+        pws.map(|pw| String::from_utf8(pw).unwrap()).collect()
+    }
+
     #[test]
     fn can_read_multiple_pw_files() {
         let password_reader = PasswordReader::build(vec![
@@ -88,7 +276,7 @@ mod tests {
         let mut pws: Vec<String> = vec![];
 
         for pw in password_reader {
-            pws.push(pw);
+            pws.push(String::from_utf8(pw).unwrap());
         }
 
         assert_eq!(pws, ["test1", "test2", "test3", "test1", "test2", "test3"]);
@@ -105,7 +293,7 @@ mod tests {
         let mut pws: Vec<String> = vec![];
 
         for pw in password_reader {
-            pws.push(pw);
+            pws.push(String::from_utf8(pw).unwrap());
         }
 
         assert_eq!(pws, ["test1", "test2"]);
@@ -122,10 +310,195 @@ mod tests {
         let mut pws: Vec<String> = vec![];
 
         for pw in password_reader {
-            pws.push(pw);
+            pws.push(String::from_utf8(pw).unwrap());
         }
 
         // Take 2 from first list, 1 from second list.
         assert_eq!(pws, ["test1", "test2", "test1"]);
     }
+
+    #[test]
+    fn can_skip_lines() {
+        let mut password_reader = PasswordReader::build(vec![
+            PwListConfiguration::new("tests/resources/pw-list.txt", Some(2)),
+            PwListConfiguration::new("tests/resources/pw-list.txt", None),
+        ])
+        .unwrap();
+
+        assert!(password_reader.skip_lines(&[2]).is_err());
+
+        password_reader.skip_lines(&[2, 1]).unwrap();
+        assert_eq!(password_reader.current_source(), Some(1));
+
+        let pws = strings(password_reader.by_ref());
+
+        assert_eq!(pws, ["test2", "test3"]);
+        assert_eq!(password_reader.line_offsets(), [2, 3]);
+        assert_eq!(password_reader.current_source(), None);
+    }
+
+    #[test]
+    fn can_skip_configured_lines() {
+        let mut password_reader = PasswordReader::build(vec![
+            PwListConfiguration::new("tests/resources/pw-list.txt", Some(1)).with_skip(1),
+            PwListConfiguration::new("tests/resources/pw-list-2.txt", None).with_skip(5),
+            PwListConfiguration::new("tests/resources/pw-list-2.txt", None).with_skip(2),
+        ])
+        .unwrap();
+
+        let pws = strings(password_reader.by_ref());
+
+        assert_eq!(pws, ["test2", "gamma"]);
+        assert_eq!(password_reader.line_offsets(), [2, 3, 3]);
+    }
+
+    #[test]
+    fn can_resume_after_skipped_lines() {
+        let configs = || {
+            vec![
+                PwListConfiguration::new("tests/resources/pw-list.txt", None),
+                PwListConfiguration::new("tests/resources/pw-list-2.txt", None).with_skip(1),
+            ]
+        };
+
+        // The skipped lines are part of the line offsets, so they are not skipped twice.
+        let mut password_reader = PasswordReader::build(configs()).unwrap();
+        password_reader.skip_lines(&[2, 1]).unwrap();
+        assert_eq!(password_reader.current_source(), Some(0));
+
+        let pws = strings(password_reader);
+        assert_eq!(pws, ["test3", "beta", "gamma"]);
+    }
+
+    #[test]
+    fn can_read_by_priority() {
+        let password_reader = PasswordReader::build(vec![
+            PwListConfiguration::new("tests/resources/pw-list.txt", Some(1)),
+            PwListConfiguration::new("tests/resources/pw-list-2.txt", Some(1)).with_priority(1),
+            PwListConfiguration::new("tests/resources/pw-list.txt", Some(2)),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            password_reader.paths(),
+            [
+                "tests/resources/pw-list-2.txt",
+                "tests/resources/pw-list.txt",
+                "tests/resources/pw-list.txt"
+            ]
+        );
+
+        let pws = strings(password_reader);
+        assert_eq!(pws, ["alpha", "test1", "test1", "test2"]);
+    }
+
+    #[test]
+    fn should_stop_at_invalid_utf8() {
+        let mut password_reader = PasswordReader::build(vec![
+            PwListConfiguration::new("tests/resources/pw-list-latin1.txt", None),
+            PwListConfiguration::new("tests/resources/pw-list.txt", None),
+        ])
+        .unwrap();
+
+        let pws = strings(password_reader.by_ref());
+        assert_eq!(pws, ["test1"]);
+
+        match password_reader.take_error() {
+            Some(RamenskyError::Encoding { path, line }) => {
+                assert_eq!(path, "tests/resources/pw-list-latin1.txt");
+                assert_eq!(line, 2);
+            }
+            _ => panic!("Reading should have failed with an encoding error."),
+        }
+    }
+
+    #[test]
+    fn can_read_compressed_lists() {
+        let lists = [
+            ("tests/resources/pw-list.txt.gz", cfg!(feature = "gzip")),
+            ("tests/resources/pw-list.txt.bz2", cfg!(feature = "bzip2")),
+            ("tests/resources/pw-list.txt.xz", cfg!(feature = "xz")),
+            ("tests/resources/pw-list.txt.zst", cfg!(feature = "zstd")),
+        ];
+
+        for (path, enabled) in lists {
+            let password_reader = PasswordReader::build(vec![PwListConfiguration::new(path, None)]);
+
+            if enabled {
+                let pws = strings(password_reader.unwrap());
+                assert_eq!(pws, ["test1", "test2", "test3"], "Reading {path}");
+            } else {
+                assert!(
+                    matches!(password_reader, Err(RamenskyError::InvalidConfiguration(_))),
+                    "Reading {path}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn can_read_embedded_list() {
+        let password_reader =
+            PasswordReader::build(vec![PwListConfiguration::embedded_common_passwords(Some(
+                10,
+            ))]);
+
+        if embedded_pw_list::is_available() {
+            let password_reader = password_reader.unwrap();
+            assert_eq!(password_reader.paths(), ["<embedded common passwords>"]);
+            assert_eq!(password_reader.count(), 10);
+        } else {
+            assert!(matches!(
+                password_reader,
+                Err(RamenskyError::InvalidConfiguration(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn can_handle_invalid_utf8() {
+        let read = |encoding| {
+            let mut password_reader = PasswordReader::build(vec![PwListConfiguration::new(
+                "tests/resources/pw-list-latin1.txt",
+                None,
+            )
+            .with_encoding(encoding)])
+            .unwrap();
+            let pws: Vec<Vec<u8>> = password_reader.by_ref().collect();
+
+            assert!(password_reader.take_error().is_none());
+            (
+                pws,
+                password_reader.skipped_lines(),
+                password_reader.converted_lines(),
+            )
+        };
+
+        let (pws, skipped, converted) = read(EncodingPolicy::Skip);
+        assert_eq!(pws, [b"test1", b"test3"]);
+        assert_eq!((skipped, converted), (1, 0));
+
+        let (pws, skipped, converted) = read(EncodingPolicy::Latin1);
+        assert_eq!(strings(pws.into_iter()), ["test1", "café", "test3"]);
+        assert_eq!((skipped, converted), (0, 1));
+
+        let (pws, _, converted) = read(EncodingPolicy::Lossy);
+        assert_eq!(pws[1], "caf\u{FFFD}".as_bytes());
+        assert_eq!(converted, 1);
+
+        let (pws, skipped, converted) = read(EncodingPolicy::Raw);
+        assert_eq!(pws[1], b"caf\xe9");
+        assert_eq!((skipped, converted), (0, 0));
+    }
+
+    #[test]
+    fn should_validate_paths() {
+        assert!(matches!(
+            PasswordReader::build(vec![PwListConfiguration::new(
+                "tests/resources/missing.txt",
+                None
+            )]),
+            Err(RamenskyError::Io { .. })
+        ));
+    }
 }
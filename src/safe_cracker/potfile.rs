@@ -0,0 +1,199 @@
+//! Module containing the potfile, that records the passwords that were found, so they do not need to be
+//! cracked again.
+//!
+//! Potfiles are plain text files, with a line for every password that was found. Every line contains the
+//! [`fingerprint`](crate::adaptor::base::BaseAdaptor::fingerprint) of the target, when the password was
+//! found, and the password, separated by tabs:
+//! ```text
+//! zip:5d41402abc4b2a76b9719d911017c592<TAB>2024-05-01T12:00:00+02:00<TAB>password
+//! ```
+//! Passwords that are not valid UTF-8, or contain tabs or line breaks, are saved as `$HEX[...]`.
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+use chrono::{DateTime, Local};
+
+use crate::error::RamenskyError;
+use crate::hex;
+use crate::safe_cracker::password::Password;
+
+const HEX_PREFIX: &str = "$HEX[";
+
+/// A password that was found, read from a potfile.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PotfileEntry {
+    /// Fingerprint of the target the password belongs to.
+    pub fingerprint: String,
+
+    /// When the password was found.
+    pub found_at: DateTime<Local>,
+
+    pub password: Password,
+}
+
+/// A potfile at the given path. The file is created once the first password is appended.
+pub struct Potfile<'a> {
+    path: &'a Path,
+}
+
+impl<'a> Potfile<'a> {
+    pub fn new<P: AsRef<Path> + ?Sized>(path: &'a P) -> Self {
+        Potfile {
+            path: path.as_ref(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.path
+    }
+
+    /// The last password that was found for the target with the given fingerprint, or [`None`] if the
+    /// target is not in the potfile. A potfile that does not exist yet is empty.
+    pub fn find(&self, fingerprint: &str) -> Result<Option<PotfileEntry>, RamenskyError> {
+        let contents = match fs::read_to_string(self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(RamenskyError::io(self.path, e)),
+        };
+
+        let mut found = None;
+
+        for (index, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let entry = self.parse(line).ok_or_else(|| {
+                RamenskyError::InvalidConfiguration(format!(
+                    "Invalid line {} in potfile {}.",
+                    index + 1,
+                    self.path.display()
+                ))
+            })?;
+
+            if entry.fingerprint == fingerprint {
+                found = Some(entry);
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Append the password of the target with the given fingerprint to the potfile.
+    pub fn append(&self, fingerprint: &str, password: &Password) -> Result<(), RamenskyError> {
+        if fingerprint.contains(['\t', '\n', '\r']) {
+            return Err(RamenskyError::InvalidConfiguration(format!(
+                "Fingerprint {fingerprint:?} cannot contain tabs or line breaks."
+            )));
+        }
+
+        let line = format!(
+            "{fingerprint}\t{}\t{}\n",
+            Local::now().to_rfc3339(),
+            encode(password)
+        );
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| RamenskyError::io(self.path, e))
+    }
+
+    fn parse(&self, line: &str) -> Option<PotfileEntry> {
+        let mut fields = line.splitn(3, '\t');
+        let fingerprint = fields.next()?.to_string();
+        let found_at = DateTime::parse_from_rfc3339(fields.next()?).ok()?;
+        let password = decode(fields.next()?)?;
+
+        Some(PotfileEntry {
+            fingerprint,
+            found_at: found_at.with_timezone(&Local),
+            password,
+        })
+    }
+}
+
+fn encode(password: &Password) -> String {
+    match password.to_str() {
+        Some(password)
+            if !password.starts_with(HEX_PREFIX) && !password.contains(['\t', '\n', '\r']) =>
+        {
+            password.to_string()
+        }
+        _ => format!("{HEX_PREFIX}{}]", hex::encode(password.as_bytes())),
+    }
+}
+
+fn decode(password: &str) -> Option<Password> {
+    let digits = match password
+        .strip_prefix(HEX_PREFIX)
+        .and_then(|digits| digits.strip_suffix(']'))
+    {
+        Some(digits) => digits,
+        None => return Some(Password::from(password)),
+    };
+
+    hex::decode(digits).map(Password::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use crate::safe_cracker::password::Password;
+    use crate::safe_cracker::potfile::Potfile;
+
+    #[test]
+    fn can_append_and_find() {
+        let path = env::temp_dir().join("ramensky-potfile-test.txt");
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path);
+        let potfile = Potfile::new(path);
+
+        assert_eq!(potfile.find("zip:1").unwrap(), None);
+
+        let passwords = [
+            Password::from("pass\tword"),
+            Password::new(b"caf\xe9".to_vec()),
+            Password::from("$HEX[00]"),
+            Password::from("test"),
+        ];
+
+        for password in &passwords {
+            potfile.append("zip:1", password).unwrap();
+            assert_eq!(potfile.find("zip:1").unwrap().unwrap().password, *password);
+        }
+
+        potfile.append("zip:2", &Password::from("other")).unwrap();
+        assert_eq!(potfile.find("zip:1").unwrap().unwrap().password, "test");
+        assert_eq!(potfile.find("zip:2").unwrap().unwrap().password, "other");
+        assert_eq!(potfile.find("zip:3").unwrap(), None);
+
+        assert!(potfile.append("zip\t4", &Password::from("test")).is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_validate_lines() {
+        let path = env::temp_dir().join("ramensky-potfile-invalid-test.txt");
+        let path = path.to_str().unwrap();
+        let potfile = Potfile::new(path);
+
+        fs::write(path, "zip:1\tyesterday\ttest\n").unwrap();
+        assert!(potfile.find("zip:1").is_err());
+
+        fs::write(path, "zip:1\t2024-05-01T12:00:00+02:00\t$HEX[0]\n").unwrap();
+        assert!(potfile.find("zip:1").is_err());
+
+        fs::write(path, "zip:1\t2024-05-01T12:00:00+02:00\t$HEX[+f]\n").unwrap();
+        assert!(potfile.find("zip:1").is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+}
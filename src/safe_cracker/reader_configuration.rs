@@ -1,10 +1,160 @@
-pub struct PwListConfiguration<'a> {
-    pub path: &'a str,
+//! Module containing the configuration of a password list, read by the `PasswordReader`.
+
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+
+use crate::error::RamenskyError;
+use crate::safe_cracker::encoding_policy::EncodingPolicy;
+
+/// Where the passwords of a password list are read from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PwListSource {
+    /// Path to a password list. The file needs to be a newline separated list of passwords.
+    File(PathBuf),
+
+    /// The common password list embedded in the library, see
+    /// [`embedded_pw_list`](crate::safe_cracker::embedded_pw_list).
+    EmbeddedCommonPasswords,
+}
+
+impl Display for PwListSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PwListSource::File(path) => write!(f, "{}", path.display()),
+            PwListSource::EmbeddedCommonPasswords => write!(f, "<embedded common passwords>"),
+        }
+    }
+}
+
+/// A password list, and which of its passwords to read.
+///
+/// In configuration files, the source is the `path` of the password list, or the embedded common password
+/// list if no path is provided.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "PwListConfigurationData", into = "PwListConfigurationData")
+)]
+pub struct PwListConfiguration {
+    /// Where the password list is read from.
+    pub source: PwListSource,
+
+    /// How many passwords to read from the list, after the skipped ones.
+    /// Set as [`None`](Option::None) to read the whole list.
     pub amount: Option<usize>,
+
+    /// How many lines to skip at the start of the list, e.g. because they were already tried.
+    pub skip: usize,
+
+    /// Lists with a higher priority are read first. Lists with the same priority are read in the
+    /// order they were provided. Defaults to 0.
+    pub priority: i32,
+
+    /// What to do with lines that are not valid UTF-8. Defaults to [`EncodingPolicy::Error`].
+    pub encoding: EncodingPolicy,
 }
 
-impl<'a> PwListConfiguration<'a> {
-    pub fn new(path: &'a str, amount: Option<usize>) -> Self {
-        PwListConfiguration { path, amount }
+impl PwListConfiguration {
+    /// Read the password list at `path`.
+    pub fn new(path: impl Into<PathBuf>, amount: Option<usize>) -> Self {
+        Self::from_source(PwListSource::File(path.into()), amount)
+    }
+
+    /// Read the common password list embedded in the library.
+    pub fn embedded_common_passwords(amount: Option<usize>) -> Self {
+        Self::from_source(PwListSource::EmbeddedCommonPasswords, amount)
+    }
+
+    pub fn from_source(source: PwListSource, amount: Option<usize>) -> Self {
+        PwListConfiguration {
+            source,
+            amount,
+            skip: 0,
+            priority: 0,
+            encoding: EncodingPolicy::default(),
+        }
+    }
+
+    /// Skip the first `skip` lines of the list.
+    pub fn with_skip(mut self, skip: usize) -> Self {
+        self.skip = skip;
+        self
+    }
+
+    /// Read the list before the ones with a lower priority.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Handle lines that are not valid UTF-8 with the given policy.
+    pub fn with_encoding(mut self, encoding: EncodingPolicy) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Check that the password list exists.
+    pub fn validate(&self) -> Result<(), RamenskyError> {
+        match &self.source {
+            PwListSource::File(path) if !path.is_file() => Err(RamenskyError::io(
+                path,
+                io::Error::new(io::ErrorKind::NotFound, "Not a valid file path."),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `PwListConfiguration` as it is saved in a configuration file.
+#[cfg(feature = "config")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PwListConfigurationData {
+    path: Option<PathBuf>,
+    #[serde(default)]
+    amount: Option<usize>,
+    #[serde(default)]
+    skip: usize,
+    #[serde(default)]
+    priority: i32,
+    #[serde(default)]
+    encoding: EncodingPolicy,
+}
+
+#[cfg(feature = "config")]
+impl From<PwListConfigurationData> for PwListConfiguration {
+    fn from(data: PwListConfigurationData) -> Self {
+        let source = match data.path {
+            Some(path) => PwListSource::File(path),
+            None => PwListSource::EmbeddedCommonPasswords,
+        };
+
+        PwListConfiguration {
+            source,
+            amount: data.amount,
+            skip: data.skip,
+            priority: data.priority,
+            encoding: data.encoding,
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<PwListConfiguration> for PwListConfigurationData {
+    fn from(configuration: PwListConfiguration) -> Self {
+        let path = match configuration.source {
+            PwListSource::File(path) => Some(path),
+            PwListSource::EmbeddedCommonPasswords => None,
+        };
+
+        PwListConfigurationData {
+            path,
+            amount: configuration.amount,
+            skip: configuration.skip,
+            priority: configuration.priority,
+            encoding: configuration.encoding,
+        }
     }
 }
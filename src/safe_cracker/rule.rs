@@ -0,0 +1,445 @@
+//! Module containing the rules that mangle passwords from the password lists, e.g. `password` into `Password1!`.
+//!
+//! The syntax is the common subset of hashcat and John the Ripper rules. A rule is a list of functions,
+//! applied one after another, optionally separated by spaces. Positions (`N`, `M`) are `0`-`9`, then `A`-`Z` for 10-35.
+//!
+//! | Function | Description                                    | Example rule | `p@ssW0rd` becomes |
+//! |----------|------------------------------------------------|--------------|--------------------|
+//! | `:`      | Do nothing                                     | `:`          | `p@ssW0rd`         |
+//! | `l`      | Lowercase all characters                       | `l`          | `p@ssw0rd`         |
+//! | `u`      | Uppercase all characters                       | `u`          | `P@SSW0RD`         |
+//! | `c`      | Capitalise the first character, lower the rest | `c`          | `P@ssw0rd`         |
+//! | `C`      | Lower the first character, capitalise the rest | `C`          | `p@SSW0RD`         |
+//! | `t`      | Toggle the case of all characters              | `t`          | `P@SSw0RD`         |
+//! | `TN`     | Toggle the case of the character at N          | `T3`         | `p@sSW0rd`         |
+//! | `E`      | Lowercase, then capitalise every word          | `E`          | `P@ssw0rd`         |
+//! | `r`      | Reverse                                        | `r`          | `dr0Wss@p`         |
+//! | `d`      | Duplicate                                      | `d`          | `p@ssW0rdp@ssW0rd` |
+//! | `pN`     | Append the word N times                        | `p1`         | `p@ssW0rdp@ssW0rd` |
+//! | `f`      | Append the reversed word                       | `f`          | `p@ssW0rddr0Wss@p` |
+//! | `{`      | Rotate left                                    | `{`          | `@ssW0rdp`         |
+//! | `}`      | Rotate right                                   | `}`          | `dp@ssW0r`         |
+//! | `$X`     | Append X                                       | `$1$!`       | `p@ssW0rd1!`       |
+//! | `^X`     | Prepend X                                      | `^1`         | `1p@ssW0rd`        |
+//! | `[`      | Delete the first character                     | `[`          | `@ssW0rd`          |
+//! | `]`      | Delete the last character                      | `]`          | `p@ssW0r`          |
+//! | `DN`     | Delete the character at N                      | `D1`         | `pssW0rd`          |
+//! | `xNM`    | Keep M characters, starting at N               | `x04`        | `p@ss`             |
+//! | `ONM`    | Delete M characters, starting at N             | `O12`        | `psW0rd`           |
+//! | `iNX`    | Insert X at N                                  | `i4-`        | `p@ss-W0rd`        |
+//! | `oNX`    | Overwrite the character at N with X            | `o0P`        | `P@ssW0rd`         |
+//! | `'N`     | Truncate to N characters                       | `'4`         | `p@ss`             |
+//! | `sXY`    | Replace every X with Y                         | `s@a`        | `passW0rd`         |
+//! | `@X`     | Remove every X                                 | `@s`         | `p@W0rd`           |
+//! | `zN`     | Duplicate the first character N times          | `z2`         | `ppp@ssW0rd`       |
+//! | `ZN`     | Duplicate the last character N times           | `Z2`         | `p@ssW0rddd`       |
+//! | `q`      | Duplicate every character                      | `q`          | `pp@@ssssWW00rrdd` |
+//! | `k`      | Swap the first two characters                  | `k`          | `@pssW0rd`         |
+//! | `K`      | Swap the last two characters                   | `K`          | `p@ssW0dr`         |
+//! | `*NM`    | Swap the characters at N and M                 | `*07`        | `d@ssW0rp`         |
+//!
+//! Functions that refer to a position outside of the word leave it unchanged.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::error::RamenskyError;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Function {
+    Nothing,
+    Lowercase,
+    Uppercase,
+    Capitalise,
+    InvertCapitalise,
+    ToggleCase,
+    ToggleAt(usize),
+    TitleCase,
+    Reverse,
+    Duplicate,
+    DuplicateTimes(usize),
+    Reflect,
+    RotateLeft,
+    RotateRight,
+    Append(char),
+    Prepend(char),
+    DeleteFirst,
+    DeleteLast,
+    DeleteAt(usize),
+    Extract(usize, usize),
+    Omit(usize, usize),
+    Insert(usize, char),
+    Overwrite(usize, char),
+    Truncate(usize),
+    Replace(char, char),
+    Purge(char),
+    DuplicateFirst(usize),
+    DuplicateLast(usize),
+    DuplicateAll,
+    SwapFront,
+    SwapBack,
+    Swap(usize, usize),
+}
+
+/// A single rule, e.g. `c $1 $!`, that turns one word into one password.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    functions: Vec<Function>,
+}
+
+impl Rule {
+    /// Parse a rule, e.g. `c $1 $!`.
+    pub fn parse(rule: &str) -> Result<Self, RamenskyError> {
+        let mut functions = vec![];
+        let mut chars = rule.chars();
+
+        while let Some(c) = chars.next() {
+            let mut char_arg = || -> Result<char, RamenskyError> {
+                match chars.next() {
+                    Some(arg) => Ok(arg),
+                    None => Err(RamenskyError::InvalidConfiguration(format!(
+                        "Function {c} of rule {rule} is missing an argument."
+                    ))),
+                }
+            };
+
+            let function = match c {
+                ' ' | '\t' => continue,
+                ':' => Function::Nothing,
+                'l' => Function::Lowercase,
+                'u' => Function::Uppercase,
+                'c' => Function::Capitalise,
+                'C' => Function::InvertCapitalise,
+                't' => Function::ToggleCase,
+                'T' => Function::ToggleAt(position(char_arg()?)?),
+                'E' => Function::TitleCase,
+                'r' => Function::Reverse,
+                'd' => Function::Duplicate,
+                'p' => Function::DuplicateTimes(position(char_arg()?)?),
+                'f' => Function::Reflect,
+                '{' => Function::RotateLeft,
+                '}' => Function::RotateRight,
+                '$' => Function::Append(char_arg()?),
+                '^' => Function::Prepend(char_arg()?),
+                '[' => Function::DeleteFirst,
+                ']' => Function::DeleteLast,
+                'D' => Function::DeleteAt(position(char_arg()?)?),
+                'x' => Function::Extract(position(char_arg()?)?, position(char_arg()?)?),
+                'O' => Function::Omit(position(char_arg()?)?, position(char_arg()?)?),
+                'i' => Function::Insert(position(char_arg()?)?, char_arg()?),
+                'o' => Function::Overwrite(position(char_arg()?)?, char_arg()?),
+                '\'' => Function::Truncate(position(char_arg()?)?),
+                's' => Function::Replace(char_arg()?, char_arg()?),
+                '@' => Function::Purge(char_arg()?),
+                'z' => Function::DuplicateFirst(position(char_arg()?)?),
+                'Z' => Function::DuplicateLast(position(char_arg()?)?),
+                'q' => Function::DuplicateAll,
+                'k' => Function::SwapFront,
+                'K' => Function::SwapBack,
+                '*' => Function::Swap(position(char_arg()?)?, position(char_arg()?)?),
+                _ => {
+                    return Err(RamenskyError::InvalidConfiguration(format!(
+                        "Rule {rule} contains unknown function {c}."
+                    )))
+                }
+            };
+
+            functions.push(function);
+        }
+
+        if functions.is_empty() {
+            return Err(RamenskyError::InvalidConfiguration(
+                "Rule cannot be empty, use : to keep the word as it is.".to_string(),
+            ));
+        }
+
+        Ok(Rule { functions })
+    }
+
+    /// Load rules from a rule file, one rule per line. Empty lines and lines starting with `#` are skipped.
+    pub fn load(path: impl AsRef<Path>) -> Result<Vec<Self>, RamenskyError> {
+        let path = path.as_ref();
+        let reader = BufReader::new(File::open(path).map_err(|e| RamenskyError::io(path, e))?);
+        let mut rules = vec![];
+
+        for line in reader.lines() {
+            let line = line.map_err(|e| RamenskyError::io(path, e))?;
+
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            rules.push(Self::parse(&line)?);
+        }
+
+        if rules.is_empty() {
+            return Err(RamenskyError::InvalidConfiguration(format!(
+                "Rule file {} does not contain any rules.",
+                path.display()
+            )));
+        }
+
+        Ok(rules)
+    }
+
+    /// Apply the rule to the given word.
+    pub fn apply(&self, word: &str) -> String {
+        let mut chars: Vec<char> = word.chars().collect();
+
+        for function in &self.functions {
+            apply_function(function, &mut chars);
+        }
+
+        chars.into_iter().collect()
+    }
+
+    /// Apply the rule to a word that is not necessarily valid UTF-8, e.g. a line of a password list that
+    /// is read with [`EncodingPolicy::Raw`](crate::safe_cracker::encoding_policy::EncodingPolicy::Raw).
+    /// Words that are not valid UTF-8 are handled as Latin-1, so every byte is a character, and their
+    /// bytes are kept as they are. Characters that are added by the rule, and are not part of Latin-1,
+    /// are encoded as UTF-8.
+    pub fn apply_bytes(&self, word: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.apply_into(word, &mut bytes);
+
+        bytes
+    }
+
+    /// Same as [`apply_bytes()`](Rule::apply_bytes), but clears `buffer` and writes the result into it,
+    /// so the buffer can be reused for every password.
+    pub fn apply_into(&self, word: &[u8], buffer: &mut Vec<u8>) {
+        buffer.clear();
+
+        let (mut chars, is_utf8): (Vec<char>, bool) = match std::str::from_utf8(word) {
+            Ok(word) => (word.chars().collect(), true),
+            Err(_) => (word.iter().map(|b| *b as char).collect(), false),
+        };
+
+        for function in &self.functions {
+            apply_function(function, &mut chars);
+        }
+
+        for c in chars {
+            match u8::try_from(c) {
+                Ok(b) if !is_utf8 => buffer.push(b),
+                _ => buffer.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            }
+        }
+    }
+}
+
+/// Parse a position, `0`-`9` then `A`-`Z` for 10-35.
+fn position(c: char) -> Result<usize, RamenskyError> {
+    match c {
+        '0'..='9' | 'A'..='Z' => Ok(c.to_digit(36).unwrap() as usize),
+        _ => Err(RamenskyError::InvalidConfiguration(format!(
+            "{c} is not a valid position, use 0-9 or A-Z."
+        ))),
+    }
+}
+
+fn toggle(c: char) -> Vec<char> {
+    if c.is_lowercase() {
+        c.to_uppercase().collect()
+    } else {
+        c.to_lowercase().collect()
+    }
+}
+
+fn lower(chars: &[char]) -> Vec<char> {
+    chars.iter().flat_map(|c| c.to_lowercase()).collect()
+}
+
+fn upper(chars: &[char]) -> Vec<char> {
+    chars.iter().flat_map(|c| c.to_uppercase()).collect()
+}
+
+fn apply_function(function: &Function, chars: &mut Vec<char>) {
+    let len = chars.len();
+
+    match *function {
+        Function::Nothing => {}
+        Function::Lowercase => *chars = lower(chars),
+        Function::Uppercase => *chars = upper(chars),
+        Function::Capitalise if len > 0 => {
+            let mut capitalised = upper(&chars[..1]);
+            capitalised.extend(lower(&chars[1..]));
+            *chars = capitalised;
+        }
+        Function::InvertCapitalise if len > 0 => {
+            let mut capitalised = lower(&chars[..1]);
+            capitalised.extend(upper(&chars[1..]));
+            *chars = capitalised;
+        }
+        Function::ToggleCase => *chars = chars.iter().flat_map(|c| toggle(*c)).collect(),
+        Function::ToggleAt(n) if n < len => {
+            chars.splice(n..n + 1, toggle(chars[n]));
+        }
+        Function::TitleCase => {
+            let mut previous = ' ';
+            *chars = lower(chars)
+                .into_iter()
+                .flat_map(|c| {
+                    let title = if previous == ' ' {
+                        c.to_uppercase().collect()
+                    } else {
+                        vec![c]
+                    };
+                    previous = c;
+                    title
+                })
+                .collect();
+        }
+        Function::Reverse => chars.reverse(),
+        Function::Duplicate => chars.extend_from_within(..),
+        Function::DuplicateTimes(n) => *chars = chars.repeat(n + 1),
+        Function::Reflect => {
+            let reversed: Vec<char> = chars.iter().rev().copied().collect();
+            chars.extend(reversed);
+        }
+        Function::RotateLeft if len > 0 => chars.rotate_left(1),
+        Function::RotateRight if len > 0 => chars.rotate_right(1),
+        Function::Append(c) => chars.push(c),
+        Function::Prepend(c) => chars.insert(0, c),
+        Function::DeleteFirst if len > 0 => {
+            chars.remove(0);
+        }
+        Function::DeleteLast => {
+            chars.pop();
+        }
+        Function::DeleteAt(n) if n < len => {
+            chars.remove(n);
+        }
+        Function::Extract(n, m) if n < len => {
+            *chars = chars[n..(n + m).min(len)].to_vec();
+        }
+        Function::Omit(n, m) if n < len => {
+            chars.drain(n..(n + m).min(len));
+        }
+        Function::Insert(n, c) if n <= len => chars.insert(n, c),
+        Function::Overwrite(n, c) if n < len => chars[n] = c,
+        Function::Truncate(n) => chars.truncate(n),
+        Function::Replace(from, to) => {
+            for c in chars.iter_mut().filter(|c| **c == from) {
+                *c = to;
+            }
+        }
+        Function::Purge(c) => chars.retain(|x| *x != c),
+        Function::DuplicateFirst(n) if len > 0 => {
+            chars.splice(0..0, vec![chars[0]; n]);
+        }
+        Function::DuplicateLast(n) if len > 0 => chars.extend(vec![chars[len - 1]; n]),
+        Function::DuplicateAll => *chars = chars.iter().flat_map(|c| [*c, *c]).collect(),
+        Function::SwapFront if len > 1 => chars.swap(0, 1),
+        Function::SwapBack if len > 1 => chars.swap(len - 2, len - 1),
+        Function::Swap(n, m) if n < len && m < len => chars.swap(n, m),
+        // Functions that refer to a position outside of the word leave it unchanged.
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::safe_cracker::rule::Rule;
+
+    fn apply(rule: &str, word: &str) -> String {
+        Rule::parse(rule).unwrap().apply(word)
+    }
+
+    #[test]
+    fn can_apply_documented_examples() {
+        let examples = [
+            (":", "p@ssW0rd"),
+            ("l", "p@ssw0rd"),
+            ("u", "P@SSW0RD"),
+            ("c", "P@ssw0rd"),
+            ("C", "p@SSW0RD"),
+            ("t", "P@SSw0RD"),
+            ("T3", "p@sSW0rd"),
+            ("E", "P@ssw0rd"),
+            ("r", "dr0Wss@p"),
+            ("d", "p@ssW0rdp@ssW0rd"),
+            ("p1", "p@ssW0rdp@ssW0rd"),
+            ("f", "p@ssW0rddr0Wss@p"),
+            ("{", "@ssW0rdp"),
+            ("}", "dp@ssW0r"),
+            ("$1$!", "p@ssW0rd1!"),
+            ("^1", "1p@ssW0rd"),
+            ("[", "@ssW0rd"),
+            ("]", "p@ssW0r"),
+            ("D1", "pssW0rd"),
+            ("x04", "p@ss"),
+            ("O12", "psW0rd"),
+            ("i4-", "p@ss-W0rd"),
+            ("o0P", "P@ssW0rd"),
+            ("'4", "p@ss"),
+            ("s@a", "passW0rd"),
+            ("@s", "p@W0rd"),
+            ("z2", "ppp@ssW0rd"),
+            ("Z2", "p@ssW0rddd"),
+            ("q", "pp@@ssssWW00rrdd"),
+            ("k", "@pssW0rd"),
+            ("K", "p@ssW0dr"),
+            ("*07", "d@ssW0rp"),
+        ];
+
+        for (rule, expected) in examples {
+            assert_eq!(apply(rule, "p@ssW0rd"), expected, "Rule {rule}");
+        }
+    }
+
+    #[test]
+    fn can_chain_functions() {
+        assert_eq!(apply("c $1 $!", "password"), "Password1!");
+        assert_eq!(apply("$2$0$2$4", "summer"), "summer2024");
+        assert_eq!(apply("sa@ se3 so0", "password"), "p@ssw0rd");
+        assert_eq!(apply("$ $x", "a"), "a x");
+    }
+
+    #[test]
+    fn can_handle_out_of_bounds_positions() {
+        assert_eq!(apply("D9", "abc"), "abc");
+        assert_eq!(apply("o5x", "abc"), "abc");
+        assert_eq!(apply("x25", "abc"), "c");
+        assert_eq!(apply("[ ] { } c k K", ""), "");
+    }
+
+    #[test]
+    fn can_handle_unicode() {
+        assert_eq!(apply("u", "straße"), "STRASSE");
+        assert_eq!(apply("c r", "çava"), "avaÇ");
+    }
+
+    #[test]
+    fn can_apply_to_bytes() {
+        let rule = Rule::parse("u $!").unwrap();
+
+        assert_eq!(rule.apply_bytes(b"caf\xc3\xa9"), "CAFÉ!".as_bytes());
+        assert_eq!(rule.apply_bytes(b"caf\xe9"), b"CAF\xc9!");
+
+        let mut buffer = b"a longer previous password".to_vec();
+        rule.apply_into(b"caf\xe9", &mut buffer);
+        assert_eq!(buffer, b"CAF\xc9!");
+    }
+
+    #[test]
+    fn can_validate() {
+        assert!(Rule::parse("").is_err());
+        assert!(Rule::parse("  ").is_err());
+        assert!(Rule::parse("$").is_err());
+        assert!(Rule::parse("x1").is_err());
+        assert!(Rule::parse("D?").is_err());
+        assert!(Rule::parse("!").is_err());
+    }
+
+    #[test]
+    fn can_load_rule_file() {
+        let rules = Rule::load("tests/resources/rules.txt").unwrap();
+
+        let pws: Vec<String> = rules.iter().map(|rule| rule.apply("password")).collect();
+
+        assert_eq!(pws, ["password", "Password", "Password1!", "p@ssw0rd"]);
+        assert!(Rule::load("tests/resources/pw-list.txt").is_err());
+    }
+}
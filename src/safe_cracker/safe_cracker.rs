@@ -1,75 +1,419 @@
 //! Module containing the password cracker.
 
-use std::error::Error;
-use std::path::Path;
-use std::time::Instant;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
 
 use crate::adaptor::attempt_result::AttemptResult;
-use crate::adaptor::base::BaseAdaptor;
-use crate::safe_cracker::brute_forcer::BruteForcer;
+use crate::adaptor::byte_adaptor::ByteAdaptor;
+use crate::error::RamenskyError;
+use crate::safe_cracker::attack::{Attack, AttackStage};
+use crate::safe_cracker::cancellation_token::CancellationToken;
+use crate::safe_cracker::candidates::{Candidates, Origin};
+use crate::safe_cracker::checkpoint::Checkpoint;
+use crate::safe_cracker::embedded_pw_list;
+use crate::safe_cracker::error_policy::ErrorPolicy;
+use crate::safe_cracker::observer::{Event, Observer, PrintObserver, Progress};
 use crate::safe_cracker::options::Options;
+use crate::safe_cracker::password::Password;
 use crate::safe_cracker::password_crack_result::PasswordCrackResult;
-use crate::safe_cracker::password_reader::PasswordReader;
-use crate::safe_cracker::reader_configuration::PwListConfiguration;
+use crate::safe_cracker::potfile::Potfile;
+use crate::safe_cracker::reader_configuration::{PwListConfiguration, PwListSource};
+use crate::safe_cracker::statistics::{PasswordSource, Statistics};
 
+/// Used if the common password list is not embedded, and no path to it is provided.
 const COMMON_PW_PATH: &str = "resources/common-passwords.txt";
 
-pub struct SafeCracker<'a> {
-    password_reader: PasswordReader,
-    brute_forcer: Option<BruteForcer>,
-    options: Options<'a>,
+pub struct SafeCracker {
+    candidates: Candidates,
+    options: Options,
+    observers: Vec<Box<dyn Observer>>,
+    cancellation_token: CancellationToken,
 }
 
-impl<'a> SafeCracker<'a> {
-    pub fn build(options: Options<'a>) -> Result<Self, Box<dyn Error>> {
-        let mut paths = vec![];
+/// Hands out passwords to the workers, and keeps track of the ones that are still being tried,
+/// so that checkpoints never skip a password.
+struct Dispatcher<'o> {
+    candidates: Candidates,
+    /// Checkpoints taken right before every password that is still being tried, by password id.
+    /// Only filled if checkpoints are enabled.
+    in_flight: Option<BTreeMap<u64, Checkpoint>>,
+    next_id: u64,
+    last_save: Instant,
+    observers: &'o [Box<dyn Observer>],
+    /// Amount of passwords tried so far, updated by the workers.
+    tried: &'o AtomicU64,
+    stage: Option<usize>,
+    started: Instant,
+    last_progress: Instant,
+    progress_interval: Duration,
+    cancellation_token: &'o CancellationToken,
+    max_duration: Option<Duration>,
+    max_attempts: Option<u64>,
+    /// Whether the run was stopped before every password was handed out.
+    interrupted: bool,
+    /// The error that stopped the passwords from being generated, if any.
+    error: Option<RamenskyError>,
+    /// Passwords that failed with an error, to be tried again.
+    requeued: VecDeque<Candidate>,
+    /// Amount of passwords tried at the last progress event.
+    last_progress_tried: u64,
+    /// Whether passwords that are not valid UTF-8 are skipped, because the adaptor only takes strings.
+    skip_raw_passwords: bool,
+    /// Amount of passwords that were skipped, because they were not valid UTF-8.
+    skipped_passwords: u64,
+    peak_attempts_per_second: f64,
+}
 
-        if options.try_common_passwords {
-            if let Some(opt) = options.common_password_options {
-                paths.push(PwListConfiguration::new(COMMON_PW_PATH, opt.amount_to_use));
-            } else {
-                return Err(
-                    "common_password_options needs to set if try_common_passwords is enabled.",
-                )?;
+/// A password handed out to a worker.
+struct Candidate {
+    id: u64,
+    pw: Vec<u8>,
+    origin: Origin,
+    /// Index of the stage of the pipeline that generated the password.
+    stage: usize,
+    /// Amount of times the password was requeued, after the adaptor returned an error.
+    requeues: u32,
+}
+
+impl<'o> Dispatcher<'o> {
+    /// Hand out the next password. The buffer of a previous password can be passed in, to be reused.
+    fn next(&mut self, mut buffer: Vec<u8>) -> Option<Candidate> {
+        if self.cancellation_token.is_cancelled()
+            || self
+                .max_duration
+                .is_some_and(|max_duration| self.started.elapsed() >= max_duration)
+            || self
+                .max_attempts
+                .is_some_and(|max_attempts| self.next_id >= max_attempts)
+        {
+            self.interrupted = true;
+            return None;
+        }
+
+        // Requeued passwords are still in flight, so they keep their checkpoint.
+        if let Some(candidate) = self.requeued.pop_front() {
+            return Some(candidate);
+        }
+
+        let checkpoint = loop {
+            let checkpoint = self
+                .in_flight
+                .as_ref()
+                .map(|_| self.candidates.checkpoint());
+            if !self.candidates.next_into(&mut buffer) {
+                if let Some(e) = self.candidates.take_error() {
+                    self.error = Some(e);
+                }
+                return None;
+            }
+
+            if self.skip_raw_passwords && std::str::from_utf8(&buffer).is_err() {
+                self.skipped_passwords += 1;
+                continue;
             }
+
+            break checkpoint;
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        if let (Some(in_flight), Some(checkpoint)) = (&mut self.in_flight, checkpoint) {
+            in_flight.insert(id, checkpoint);
         }
 
-        if let Some(custom_path) = options.custom_pw_list_path {
-            if !Path::new(custom_path).is_file() {
-                return Err(format!(
-                    "Custom password file path {custom_path} is not a valid file path."
-                ))?;
+        // Events are sent while holding the lock, so they arrive in order.
+        if self.candidates.stage() != self.stage {
+            self.stage = self.candidates.stage();
+
+            if let Some(stage) = self.stage {
+                notify(
+                    self.observers,
+                    &Event::StageStarted(stage, self.candidates.attack(stage)),
+                );
             }
-            paths.push(PwListConfiguration::new(COMMON_PW_PATH, None));
         }
 
-        let brute_forcer;
+        if self.last_progress.elapsed() >= self.progress_interval {
+            let progress = self.progress();
+            let interval = self.last_progress.elapsed().as_secs_f64();
 
-        if options.try_brute_forcing {
-            if let Some(opt) = options.brute_forcing_options {
-                brute_forcer = Some(BruteForcer::new(opt));
-            } else {
-                return Err("brute_forcing_options needs to set if try_brute_forcing is enabled.")?;
+            if interval > 0.0 {
+                let attempts_per_second =
+                    (progress.tried - self.last_progress_tried) as f64 / interval;
+                self.peak_attempts_per_second =
+                    self.peak_attempts_per_second.max(attempts_per_second);
+            }
+
+            notify(self.observers, &Event::Progress(progress));
+            self.last_progress = Instant::now();
+            self.last_progress_tried = self.tried.load(Ordering::Relaxed);
+        }
+
+        // Every generated password has an origin.
+        let origin = self.candidates.origin()?;
+
+        Some(Candidate {
+            id,
+            pw: buffer,
+            origin,
+            stage: origin.stage,
+            requeues: 0,
+        })
+    }
+
+    /// Hand out up to `size` passwords, reusing the given buffers.
+    fn next_batch(&mut self, size: usize, buffers: &mut Vec<Vec<u8>>) -> Vec<Candidate> {
+        let mut batch = Vec::with_capacity(size);
+
+        while batch.len() < size {
+            match self.next(buffers.pop().unwrap_or_default()) {
+                Some(candidate) => batch.push(candidate),
+                None => break,
             }
-        } else {
-            brute_forcer = None
+        }
+
+        batch
+    }
+
+    fn requeue(&mut self, mut candidate: Candidate) {
+        candidate.requeues += 1;
+        self.requeued.push_back(candidate);
+    }
+
+    fn finish(&mut self, id: u64) {
+        if let Some(in_flight) = &mut self.in_flight {
+            in_flight.remove(&id);
+        }
+    }
+
+    /// Checkpoint of the oldest password that has not been tried yet.
+    fn checkpoint(&self) -> Checkpoint {
+        self.in_flight
+            .as_ref()
+            .and_then(|in_flight| in_flight.values().next().cloned())
+            .unwrap_or_else(|| self.candidates.checkpoint())
+    }
+
+    fn progress(&self) -> Progress {
+        let tried = self.tried.load(Ordering::Relaxed);
+        let elapsed = self.started.elapsed();
+
+        Progress {
+            tried,
+            attempts_per_second: tried as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+            // Passwords that were handed out, but not tried yet, are still remaining.
+            remaining: self
+                .candidates
+                .remaining()
+                .map(|remaining| remaining + (self.next_id - tried) as u128),
+            elapsed,
+        }
+    }
+}
+
+/// Outcome of trying a batch of passwords, after the [`ErrorPolicy`] was applied.
+enum Attempt {
+    /// Contains the index of the correct password, if it was found.
+    Done(Option<usize>),
+    Requeue,
+}
+
+/// Try the batch of passwords, and apply the error policy to the errors of the adaptor.
+/// Returns the error if the run needs to be stopped.
+fn try_batch<T: ByteAdaptor>(
+    adaptor: &T,
+    batch: &[Candidate],
+    error_policy: ErrorPolicy,
+    errors: &AtomicU64,
+    observers: &[Box<dyn Observer>],
+) -> Result<Attempt, RamenskyError> {
+    let mut retries = 0;
+    let requeues = batch.iter().map(|c| c.requeues).max().unwrap_or(0);
+
+    loop {
+        let result = match batch {
+            [candidate] => adaptor
+                .try_password_bytes(&candidate.pw)
+                .map(|result| matches!(result, AttemptResult::Success).then_some(0)),
+            _ => {
+                let passwords: Vec<&[u8]> = batch.iter().map(|c| c.pw.as_slice()).collect();
+                adaptor.try_passwords_bytes(&passwords)
+            }
+        };
+
+        let e = match result {
+            Ok(found) => return Ok(Attempt::Done(found)),
+            Err(RamenskyError::Cancelled) => return Err(RamenskyError::Cancelled),
+            Err(e) => e,
+        };
+
+        errors.fetch_add(1, Ordering::Relaxed);
+        notify(observers, &Event::AdaptorError(&e));
+
+        match error_policy {
+            ErrorPolicy::Skip => return Ok(Attempt::Done(None)),
+            ErrorPolicy::Retry {
+                retries: max_retries,
+                backoff,
+            } if retries < max_retries => {
+                thread::sleep(backoff.saturating_mul(2u32.saturating_pow(retries)));
+                retries += 1;
+            }
+            ErrorPolicy::Requeue {
+                pause,
+                max_requeues,
+            } if requeues < max_requeues => {
+                thread::sleep(pause);
+                return Ok(Attempt::Requeue);
+            }
+            _ => return Err(e),
+        }
+    }
+}
+
+fn notify(observers: &[Box<dyn Observer>], event: &Event) {
+    for observer in observers {
+        observer.on_event(event);
+    }
+}
+
+/// The pipeline used if none is provided: the common password list and the custom password lists by
+/// priority, with the rules applied if provided, and then the brute forcing.
+fn default_pipeline(options: &Options) -> Vec<AttackStage> {
+    let mut lists = vec![];
+
+    if options.try_common_passwords {
+        if let Some(opt) = &options.common_password_options {
+            let source = match &options.common_pw_list_path {
+                Some(path) => PwListSource::File(path.clone()),
+                None if embedded_pw_list::is_available() => PwListSource::EmbeddedCommonPasswords,
+                None => PwListSource::File(PathBuf::from(COMMON_PW_PATH)),
+            };
+            lists.push(PwListConfiguration::from_source(source, opt.amount_to_use));
+        }
+    }
+
+    lists.extend(options.custom_pw_lists.iter().cloned());
+    lists.sort_by_key(|list| Reverse(list.priority));
+
+    let mut pipeline: Vec<AttackStage> = lists
+        .into_iter()
+        .map(|list| match &options.rules_path {
+            Some(rules_path) => Attack::Rules {
+                list,
+                rules_path: rules_path.clone(),
+            },
+            None => Attack::Wordlist { list },
+        })
+        .map(AttackStage::new)
+        .collect();
+
+    if let (true, Some(opt)) = (options.try_brute_forcing, &options.brute_forcing_options) {
+        pipeline.push(AttackStage::new(Attack::BruteForce {
+            options: opt.clone(),
+        }));
+    }
+
+    pipeline
+}
+
+impl SafeCracker {
+    pub fn build(options: Options) -> Result<Self, RamenskyError> {
+        options.validate()?;
+
+        let pipeline = match options.pipeline.is_empty() {
+            true => default_pipeline(&options),
+            false => options.pipeline.clone(),
+        };
+
+        let mut observers: Vec<Box<dyn Observer>> = vec![];
+
+        if !options.quiet {
+            observers.push(Box::new(PrintObserver));
         }
 
         Ok(Self {
-            password_reader: PasswordReader::build(paths)?,
-            brute_forcer,
+            candidates: Candidates::build(pipeline)?,
             options,
+            observers,
+            cancellation_token: CancellationToken::new(),
         })
     }
+
+    /// Notify the given observer about every [`Event`] of the run, e.g. to render a progress bar.
+    /// Can be called multiple times to add multiple observers.
+    pub fn with_observer(mut self, observer: impl Observer + 'static) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    /// Stop the run once the given token is cancelled, e.g. from another thread.
+    /// The run will then return [`PasswordCrackResult::Interrupted`].
+    pub fn with_cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
+    /// Build a `SafeCracker` that continues from the given checkpoint, instead of starting from scratch.
+    /// The options need to be the same as the ones used for the run that saved the checkpoint.
+    ///
+    /// Example usage:
+    /// ```rust,no_run
+    /// use ramensky::safe_cracker::checkpoint::Checkpoint;
+    /// use ramensky::safe_cracker::options::Options;
+    /// use ramensky::safe_cracker::safe_cracker::SafeCracker;
+    ///
+    /// let options = Options::builder()
+    ///     .checkpoint_path("checkpoint.txt")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let checkpoint = Checkpoint::load("checkpoint.txt").unwrap();
+    /// let safe_cracker = SafeCracker::resume(options, &checkpoint).unwrap();
+    /// ```
+    pub fn resume(options: Options, checkpoint: &Checkpoint) -> Result<Self, RamenskyError> {
+        let mut safe_cracker = Self::build(options)?;
+        safe_cracker.candidates.resume(checkpoint)?;
+
+        Ok(safe_cracker)
+    }
+
     /// Start password cracking.
     /// This method will generate passwords, and call the adaptors `try_password()` method for each
     /// password.
     ///
-    /// The execution order is:
-    /// 1. Most common 10M passwords (skipped if not enabled in [Options](Options))
-    /// 2. Custom password list (skipped if not provided in [Options](Options))
+    /// Passwords are tried by [`threads`](Options::threads) workers in parallel. All workers stop as
+    /// soon as one of them finds the password, the run is cancelled, or the
+    /// [`error_policy`](Options::error_policy) aborts on an error of the adaptor.
+    /// If the adaptor returns [`RamenskyError::Cancelled`], the run is interrupted.
+    ///
+    /// If [`checkpoint_path`](Options::checkpoint_path) is provided, a [`Checkpoint`] is saved there
+    /// every [`checkpoint_interval`](Options::checkpoint_interval), and when the run stops on an error.
+    /// The run can then be continued with [`resume()`](SafeCracker::resume).
+    ///
+    /// The run is interrupted if the [`CancellationToken`] is cancelled, or the
+    /// [`max_duration`](Options::max_duration) or [`max_attempts`](Options::max_attempts) is reached.
+    ///
+    /// Progress is reported to the observers added with [`with_observer()`](SafeCracker::with_observer).
+    ///
+    /// If [`pipeline`](Options::pipeline) is not empty, its stages are run in order, each until its
+    /// [`max_attempts`](AttackStage::max_attempts) or [`max_duration`](AttackStage::max_duration) is
+    /// reached. Otherwise, the default pipeline is:
+    /// 1. Most common 1M passwords (skipped if not enabled in [Options](Options))
+    /// 2. Custom password lists, by priority (skipped if not provided in [Options](Options))
     /// 3. Brute forcing (skipped if not enabled in [Options](Options))
     ///
+    /// If [`rules_path`](Options::rules_path) is provided, every rule is applied to every password
+    /// from the password lists, before moving on to the next password.
+    ///
     /// Example usage:
     /// ```rust
     /// use ramensky::adaptor::custom::test_adaptor::TestAdaptor;
@@ -81,58 +425,267 @@ impl<'a> SafeCracker<'a> {
     /// let safe_cracker = SafeCracker::build(Options::default()).unwrap();
     /// let adaptor = TestAdaptor::without_delay("abcde");
     /// match safe_cracker.start(adaptor).unwrap() {
-    ///     PasswordCrackResult::Success(pw, elapsed) => println!("Success! Password is {pw}. Execution took {} seconds", elapsed.as_secs()),
-    ///     PasswordCrackResult::Failure(elapsed) => println!("Failure. Execution took {} seconds", elapsed.as_secs()),
+    ///     PasswordCrackResult::Success(pw, stats) => println!("Success! Password is {pw}. Execution took {} seconds", stats.elapsed.as_secs()),
+    ///     PasswordCrackResult::Failure(stats) => println!("Failure. Execution took {} seconds", stats.elapsed.as_secs()),
+    ///     PasswordCrackResult::Interrupted(_, stats) => println!("Interrupted after {} passwords. Execution took {} seconds", stats.attempts, stats.elapsed.as_secs()),
     /// }
     /// ```
-    pub fn start<T: BaseAdaptor>(self, adaptor: T) -> Result<PasswordCrackResult, Box<dyn Error>> {
-        macro_rules! print_with_ts {
-            ($F:expr) => {
-                if !self.options.quiet {
-                    println!("{:?}\t{}", chrono::offset::Local::now(), $F);
-                }
-            };
+    pub fn start<T: ByteAdaptor + Sync>(
+        self,
+        adaptor: T,
+    ) -> Result<PasswordCrackResult, RamenskyError> {
+        let observers = &self.observers[..];
+        notify(observers, &Event::Started);
+
+        let now = Instant::now();
+        let started_at = Local::now();
+
+        let potfile = self.options.potfile_path.as_ref().map(Potfile::new);
+        let fingerprint = potfile.as_ref().and(adaptor.fingerprint());
+
+        if let (Some(potfile), Some(fingerprint)) = (&potfile, &fingerprint) {
+            if let Some(entry) = potfile.find(fingerprint)? {
+                let mut statistics = Statistics::empty(started_at);
+                statistics.found_by = Some(PasswordSource::Potfile {
+                    path: potfile.path().display().to_string(),
+                    found_at: entry.found_at,
+                });
+
+                notify(observers, &Event::Success(&entry.password, &statistics));
+                return Ok(PasswordCrackResult::Success(entry.password, statistics));
+            }
         }
 
-        print_with_ts!("Starting attempt");
+        let checkpoint_path = self.options.checkpoint_path.as_deref();
+        let checkpoint_interval = self.options.checkpoint_interval;
 
-        let now = Instant::now();
+        let error_policy = self.options.error_policy;
+        let batch_size = self.options.batch_size;
 
-        for pw in self.password_reader {
-            print_with_ts!(format!("Trying password {pw}"));
+        let tried = AtomicU64::new(0);
+        let errors = AtomicU64::new(0);
+        // Indexed by the stage of the pipeline.
+        let stage_attempts: Vec<AtomicU64> = (0..self.candidates.stage_count())
+            .map(|_| AtomicU64::new(0))
+            .collect();
+        let dispatcher = Mutex::new(Dispatcher {
+            candidates: self.candidates,
+            in_flight: checkpoint_path.map(|_| BTreeMap::new()),
+            next_id: 0,
+            last_save: now,
+            observers,
+            tried: &tried,
+            stage: None,
+            started: now,
+            last_progress: now,
+            progress_interval: self.options.progress_interval,
+            cancellation_token: &self.cancellation_token,
+            max_duration: self.options.max_duration,
+            max_attempts: self.options.max_attempts,
+            interrupted: false,
+            error: None,
+            requeued: VecDeque::new(),
+            last_progress_tried: 0,
+            peak_attempts_per_second: 0.0,
+            skip_raw_passwords: !adaptor.accepts_raw_passwords(),
+            skipped_passwords: 0,
+        });
+        let stop = AtomicBool::new(false);
+        let found: Mutex<Option<(Vec<u8>, Origin)>> = Mutex::new(None);
+        let error: Mutex<Option<RamenskyError>> = Mutex::new(None);
 
-            let result = adaptor.try_password(&pw)?;
+        thread::scope(|scope| {
+            for _ in 0..self.options.threads {
+                scope.spawn(|| {
+                    // Every worker reuses the buffers of its last batch, unless they were kept.
+                    let mut buffers = vec![];
 
-            match result {
-                AttemptResult::Success => {
-                    print_with_ts!(format!("Success! {pw} is the password."));
-                    print_with_ts!(format!(
-                        "Execution took {} seconds.",
-                        now.elapsed().as_secs()
-                    ));
-
-                    return Ok(PasswordCrackResult::Success(pw, now.elapsed()));
-                }
-                AttemptResult::Failure => continue,
+                    while !stop.load(Ordering::Relaxed) {
+                        // Only hold the lock while generating the passwords, so other workers can
+                        // try theirs in the meantime.
+                        let mut batch = dispatcher
+                            .lock()
+                            .unwrap()
+                            .next_batch(batch_size, &mut buffers);
+
+                        if batch.is_empty() {
+                            break;
+                        }
+
+                        for candidate in &batch {
+                            notify(
+                                observers,
+                                &Event::Attempt(&String::from_utf8_lossy(&candidate.pw)),
+                            );
+                        }
+
+                        let result = try_batch(&adaptor, &batch, error_policy, &errors, observers);
+
+                        if let Ok(Attempt::Done(index)) = result {
+                            // The passwords after the correct one are not tried.
+                            let tried_batch =
+                                &batch[..index.map_or(batch.len(), |index| index + 1)];
+                            tried.fetch_add(tried_batch.len() as u64, Ordering::Relaxed);
+
+                            for candidate in tried_batch {
+                                stage_attempts[candidate.stage].fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+
+                        match result {
+                            Ok(Attempt::Done(Some(index))) => {
+                                let candidate = batch.swap_remove(index);
+                                found
+                                    .lock()
+                                    .unwrap()
+                                    .get_or_insert((candidate.pw, candidate.origin));
+                                stop.store(true, Ordering::Relaxed);
+                            }
+                            Ok(Attempt::Done(None)) => {
+                                let path = match checkpoint_path {
+                                    Some(path) => path,
+                                    None => {
+                                        buffers.extend(batch.into_iter().map(|c| c.pw));
+                                        continue;
+                                    }
+                                };
+
+                                let mut dispatcher = dispatcher.lock().unwrap();
+
+                                for candidate in batch {
+                                    dispatcher.finish(candidate.id);
+                                    buffers.push(candidate.pw);
+                                }
+
+                                if dispatcher.last_save.elapsed() >= checkpoint_interval {
+                                    match dispatcher.checkpoint().save(path) {
+                                        Ok(()) => notify(observers, &Event::CheckpointSaved(path)),
+                                        Err(e) => {
+                                            error.lock().unwrap().get_or_insert(e);
+                                            stop.store(true, Ordering::Relaxed);
+                                        }
+                                    }
+                                    dispatcher.last_save = Instant::now();
+                                }
+                            }
+                            Ok(Attempt::Requeue) => {
+                                let mut dispatcher = dispatcher.lock().unwrap();
+
+                                for candidate in batch {
+                                    dispatcher.requeue(candidate);
+                                }
+                            }
+                            // The passwords stay in flight, so they are tried again after resuming.
+                            Err(RamenskyError::Cancelled) => {
+                                dispatcher.lock().unwrap().interrupted = true;
+                                stop.store(true, Ordering::Relaxed);
+                            }
+                            Err(e) => {
+                                error.lock().unwrap().get_or_insert(e);
+                                stop.store(true, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut dispatcher = dispatcher.into_inner().unwrap();
+        let error = error.into_inner().unwrap().or(dispatcher.error.take());
+
+        if let Some(e) = error {
+            if let Some(path) = checkpoint_path {
+                dispatcher.checkpoint().save(path)?;
+                notify(observers, &Event::CheckpointSaved(path));
             }
+
+            return Err(e);
         }
 
-        print_with_ts!("Failure! Could not find the password.");
-        print_with_ts!(format!(
-            "Execution took {} seconds.",
-            now.elapsed().as_secs()
-        ));
-        Ok(PasswordCrackResult::Failure(now.elapsed()))
+        let found = found.into_inner().unwrap();
+        let attempts = tried.load(Ordering::Relaxed);
+        let elapsed = now.elapsed();
+        let average_attempts_per_second = attempts as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+
+        let statistics = Statistics {
+            started_at,
+            finished_at: Local::now(),
+            elapsed,
+            attempts,
+            stage_attempts: stage_attempts
+                .into_iter()
+                .map(AtomicU64::into_inner)
+                .collect(),
+            errors: errors.load(Ordering::Relaxed),
+            average_attempts_per_second,
+            // Runs that are shorter than the progress interval are never measured.
+            peak_attempts_per_second: dispatcher
+                .peak_attempts_per_second
+                .max(average_attempts_per_second),
+            skipped_lines: dispatcher.candidates.skipped_lines() + dispatcher.skipped_passwords,
+            converted_lines: dispatcher.candidates.converted_lines(),
+            found_by: found
+                .as_ref()
+                .map(|(_, origin)| dispatcher.candidates.source_of(*origin)),
+        };
+
+        if let Some((pw, _)) = found {
+            let pw = Password::new(pw);
+
+            if let (Some(potfile), Some(fingerprint)) = (&potfile, &fingerprint) {
+                potfile.append(fingerprint, &pw)?;
+            }
+
+            notify(observers, &Event::Success(&pw, &statistics));
+            return Ok(PasswordCrackResult::Success(pw, statistics));
+        }
+
+        if dispatcher.interrupted {
+            // Every worker has finished its password, so the checkpoint does not skip any.
+            let checkpoint = dispatcher.checkpoint();
+
+            if let Some(path) = checkpoint_path {
+                checkpoint.save(path)?;
+                notify(observers, &Event::CheckpointSaved(path));
+            }
+
+            notify(observers, &Event::Interrupted(&statistics));
+            return Ok(PasswordCrackResult::Interrupted(checkpoint, statistics));
+        }
+
+        notify(observers, &Event::Failure(&statistics));
+        Ok(PasswordCrackResult::Failure(statistics))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::env;
+    use std::fs;
+    use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::adaptor::attempt_result::AttemptResult;
+    use crate::adaptor::base::BaseAdaptor;
+    use crate::adaptor::byte_adaptor::ByteAdaptor;
     use crate::adaptor::custom::test_adaptor::TestAdaptor;
+    use crate::error::RamenskyError;
+    use crate::safe_cracker::attack::{Attack, AttackStage};
+    use crate::safe_cracker::brute_force_options::BruteForceOptions;
+    use crate::safe_cracker::cancellation_token::CancellationToken;
+    use crate::safe_cracker::checkpoint::Checkpoint;
     use crate::safe_cracker::common_pw_list_options::CommonPwListOptions;
+    use crate::safe_cracker::encoding_policy::EncodingPolicy;
+    use crate::safe_cracker::error_policy::ErrorPolicy;
+    use crate::safe_cracker::observer::Event;
     use crate::safe_cracker::options::Options;
     use crate::safe_cracker::password_crack_result::PasswordCrackResult;
+    use crate::safe_cracker::potfile::Potfile;
+    use crate::safe_cracker::reader_configuration::PwListConfiguration;
     use crate::safe_cracker::safe_cracker::SafeCracker;
+    use crate::safe_cracker::statistics::PasswordSource;
 
     #[test]
     fn should_find_in_common_passwords() {
@@ -142,7 +695,7 @@ mod tests {
             Some(CommonPwListOptions::default()),
             false,
             None,
-            None,
+            vec![],
         ))
         .unwrap();
 
@@ -154,9 +707,8 @@ mod tests {
         match result {
             PasswordCrackResult::Success(pw, _) => {
                 assert_eq!(pw, "qwerty");
-                assert!(true);
             }
-            PasswordCrackResult::Failure(_) => assert!(false),
+            _ => panic!("Password should have been found."),
         }
     }
 
@@ -168,15 +720,44 @@ mod tests {
             "/tests/resources/pw-list-wrong.txt"
         );
 
-        assert!(SafeCracker::build(Options::new(
+        assert!(matches!(
+            SafeCracker::build(Options::new(
+                true,
+                false,
+                None,
+                false,
+                None,
+                vec![PwListConfiguration::new(custom_path, None)]
+            )),
+            Err(RamenskyError::Io { .. })
+        ))
+    }
+
+    #[test]
+    fn should_find_in_common_pw_list_path() {
+        let mut options = Options::new(
             true,
+            true,
+            Some(CommonPwListOptions::default()),
             false,
             None,
-            false,
-            None,
-            Some(custom_path)
-        ))
-        .is_err())
+            vec![],
+        );
+        options.common_pw_list_path =
+            Some(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources/pw-list-2.txt").into());
+        let safe_cracker = SafeCracker::build(options).unwrap();
+
+        let result = safe_cracker
+            .start(TestAdaptor::without_delay("gamma"))
+            .unwrap();
+
+        match result {
+            PasswordCrackResult::Success(pw, statistics) => {
+                assert_eq!(pw, "gamma");
+                assert_eq!(statistics.stage_attempts, [3]);
+            }
+            _ => panic!("Password should have been found."),
+        }
     }
 
     #[test]
@@ -188,7 +769,7 @@ mod tests {
             None,
             false,
             None,
-            Some(custom_path),
+            vec![PwListConfiguration::new(custom_path, None)],
         ))
         .unwrap();
 
@@ -200,9 +781,872 @@ mod tests {
         match result {
             PasswordCrackResult::Success(pw, _) => {
                 assert_eq!(pw, "test2");
-                assert!(true);
             }
-            PasswordCrackResult::Failure(_) => assert!(false),
+            _ => panic!("Password should have been found."),
+        }
+    }
+
+    /// Adaptor that records every password tried, to check the order of the password lists.
+    struct RecordingAdaptor<'a> {
+        passwords: &'a Mutex<Vec<String>>,
+    }
+
+    impl<'a> BaseAdaptor for RecordingAdaptor<'a> {
+        fn try_password(&self, password: &str) -> Result<AttemptResult, RamenskyError> {
+            self.passwords.lock().unwrap().push(password.to_string());
+            Ok(AttemptResult::Failure)
+        }
+    }
+
+    fn custom_list_options(custom_pw_lists: Vec<PwListConfiguration>) -> Options {
+        Options::new(true, false, None, false, None, custom_pw_lists)
+    }
+
+    #[test]
+    fn should_read_custom_lists_by_priority() {
+        let first = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources/pw-list.txt");
+        let second = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources/pw-list-2.txt");
+        let safe_cracker = SafeCracker::build(custom_list_options(vec![
+            PwListConfiguration::new(first, Some(1)).with_skip(1),
+            PwListConfiguration::new(second, None).with_priority(1),
+        ]))
+        .unwrap();
+
+        let passwords = Mutex::new(vec![]);
+        let result = safe_cracker
+            .start(RecordingAdaptor {
+                passwords: &passwords,
+            })
+            .unwrap();
+
+        match result {
+            PasswordCrackResult::Failure(statistics) => {
+                assert_eq!(statistics.stage_attempts, [3, 1]);
+            }
+            _ => panic!("Password should not have been found."),
+        }
+        assert_eq!(
+            passwords.into_inner().unwrap(),
+            ["alpha", "beta", "gamma", "test2"]
+        );
+    }
+
+    #[test]
+    fn should_run_pipeline_in_order() {
+        let first = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources/pw-list.txt");
+        let second = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources/pw-list-2.txt");
+
+        // The pipeline replaces the custom password lists and the brute forcing.
+        let mut options = brute_force_options(1);
+        options.custom_pw_lists = vec![PwListConfiguration::new(first, None)];
+        options.pipeline = vec![
+            AttackStage::new(Attack::Mask {
+                mask: "?d".to_string(),
+                mask_charsets: vec![],
+            })
+            .with_max_attempts(2),
+            AttackStage::new(Attack::Hybrid {
+                list: PwListConfiguration::new(second, None),
+                mask: "?1".to_string(),
+                mask_charsets: vec!["12".to_string()],
+                mask_first: false,
+            }),
+        ];
+
+        let passwords = Mutex::new(vec![]);
+        let result = SafeCracker::build(options.clone())
+            .unwrap()
+            .start(RecordingAdaptor {
+                passwords: &passwords,
+            })
+            .unwrap();
+
+        match result {
+            PasswordCrackResult::Failure(statistics) => {
+                assert_eq!(statistics.stage_attempts, [2, 6]);
+            }
+            _ => panic!("Password should not have been found."),
+        }
+        assert_eq!(
+            passwords.into_inner().unwrap(),
+            ["0", "1", "alpha1", "alpha2", "beta1", "beta2", "gamma1", "gamma2"]
+        );
+
+        let result = SafeCracker::build(options)
+            .unwrap()
+            .start(TestAdaptor::without_delay("beta2"))
+            .unwrap();
+
+        match result {
+            PasswordCrackResult::Success(_, statistics) => {
+                assert_eq!(
+                    statistics.found_by,
+                    Some(PasswordSource::Hybrid {
+                        stage: 1,
+                        path: second.to_string(),
+                        line: 2,
+                        mask_index: 1,
+                    })
+                );
+            }
+            _ => panic!("Password should have been found."),
+        }
+    }
+
+    #[test]
+    fn should_report_custom_list_source() {
+        let first = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources/pw-list.txt");
+        let second = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources/pw-list-2.txt");
+        let safe_cracker = SafeCracker::build(custom_list_options(vec![
+            PwListConfiguration::new(first, None),
+            PwListConfiguration::new(second, None),
+        ]))
+        .unwrap();
+
+        let result = safe_cracker
+            .start(TestAdaptor::without_delay("beta"))
+            .unwrap();
+
+        match result {
+            PasswordCrackResult::Success(pw, statistics) => {
+                assert_eq!(pw, "beta");
+                assert_eq!(
+                    statistics.found_by,
+                    Some(PasswordSource::PasswordList {
+                        stage: 1,
+                        path: second.to_string(),
+                        line: 2,
+                        rule_index: None,
+                    })
+                );
+            }
+            _ => panic!("Password should have been found."),
+        }
+    }
+
+    #[test]
+    fn should_decode_custom_lists() {
+        let latin1 = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/resources/pw-list-latin1.txt"
+        );
+        let safe_cracker = SafeCracker::build(custom_list_options(vec![
+            PwListConfiguration::new(latin1, None).with_encoding(EncodingPolicy::Skip),
+            PwListConfiguration::new(latin1, None).with_encoding(EncodingPolicy::Windows1252),
+        ]))
+        .unwrap();
+
+        let result = safe_cracker
+            .start(TestAdaptor::without_delay("café"))
+            .unwrap();
+
+        match result {
+            PasswordCrackResult::Success(pw, statistics) => {
+                assert_eq!(pw, "café");
+                assert_eq!(statistics.skipped_lines, 1);
+                assert_eq!(statistics.converted_lines, 1);
+            }
+            _ => panic!("Password should have been found."),
+        }
+    }
+
+    #[test]
+    fn should_not_try_raw_passwords_with_string_adaptors() {
+        let latin1 = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/resources/pw-list-latin1.txt"
+        );
+        let safe_cracker = SafeCracker::build(custom_list_options(vec![PwListConfiguration::new(
+            latin1, None,
+        )
+        .with_encoding(EncodingPolicy::Raw)]))
+        .unwrap();
+
+        let passwords = Mutex::new(vec![]);
+        let result = safe_cracker
+            .start(RecordingAdaptor {
+                passwords: &passwords,
+            })
+            .unwrap();
+
+        match result {
+            PasswordCrackResult::Failure(statistics) => {
+                assert_eq!(statistics.attempts, 2);
+                assert_eq!(statistics.skipped_lines, 1);
+            }
+            _ => panic!("Password should not have been found."),
+        }
+        assert_eq!(passwords.into_inner().unwrap(), ["test1", "test3"]);
+    }
+
+    /// Adaptor that takes the password as bytes.
+    struct BytesAdaptor<'a> {
+        password: &'a [u8],
+    }
+
+    impl<'a> ByteAdaptor for BytesAdaptor<'a> {
+        fn try_password_bytes(&self, password: &[u8]) -> Result<AttemptResult, RamenskyError> {
+            if password == self.password {
+                Ok(AttemptResult::Success)
+            } else {
+                Ok(AttemptResult::Failure)
+            }
+        }
+    }
+
+    #[test]
+    fn should_try_raw_passwords_with_byte_adaptors() {
+        let latin1 = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/resources/pw-list-latin1.txt"
+        );
+        let safe_cracker = SafeCracker::build(custom_list_options(vec![PwListConfiguration::new(
+            latin1, None,
+        )
+        .with_encoding(EncodingPolicy::Raw)]))
+        .unwrap();
+
+        let result = safe_cracker
+            .start(BytesAdaptor {
+                password: b"caf\xe9",
+            })
+            .unwrap();
+
+        match result {
+            PasswordCrackResult::Success(pw, _) => {
+                assert_eq!(pw.as_bytes(), b"caf\xe9");
+                assert_eq!(pw.to_str(), None);
+            }
+            _ => panic!("Password should have been found."),
         }
     }
+
+    #[test]
+    fn should_validate_custom_lists() {
+        assert!(matches!(
+            SafeCracker::build(custom_list_options(vec![PwListConfiguration::new(
+                "tests/resources/missing.txt",
+                None
+            )])),
+            Err(RamenskyError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn should_find_by_brute_forcing() {
+        let safe_cracker = SafeCracker::build(Options::new(
+            true,
+            false,
+            None,
+            true,
+            Some(BruteForceOptions::build(1, 3, true, true, false).unwrap()),
+            vec![],
+        ))
+        .unwrap();
+
+        let adaptor = TestAdaptor::without_delay("a1");
+
+        let result = safe_cracker.start(adaptor).unwrap();
+
+        match result {
+            PasswordCrackResult::Success(pw, _) => {
+                assert_eq!(pw, "a1");
+            }
+            _ => panic!("Password should have been found."),
+        }
+    }
+
+    #[test]
+    fn should_fail_when_brute_forcing_is_exhausted() {
+        let safe_cracker = SafeCracker::build(Options::new(
+            true,
+            false,
+            None,
+            true,
+            Some(BruteForceOptions::build(1, 2, true, false, false).unwrap()),
+            vec![],
+        ))
+        .unwrap();
+
+        // Letters are not part of the charset.
+        let adaptor = TestAdaptor::without_delay("ab");
+
+        assert!(matches!(
+            safe_cracker.start(adaptor).unwrap(),
+            PasswordCrackResult::Failure(_)
+        ));
+    }
+
+    /// Adaptor that counts the attempts made, to check whether workers stop early, or whether the potfile
+    /// is used if it has a fingerprint.
+    struct CountingAdaptor<'a> {
+        password: &'a str,
+        attempts: &'a AtomicUsize,
+        fingerprint: Option<&'a str>,
+    }
+
+    impl<'a> BaseAdaptor for CountingAdaptor<'a> {
+        fn try_password(&self, password: &str) -> Result<AttemptResult, RamenskyError> {
+            self.attempts.fetch_add(1, Ordering::Relaxed);
+
+            if password == self.password {
+                Ok(AttemptResult::Success)
+            } else {
+                Ok(AttemptResult::Failure)
+            }
+        }
+
+        fn fingerprint(&self) -> Option<String> {
+            self.fingerprint.map(str::to_string)
+        }
+    }
+
+    fn brute_force_options(threads: usize) -> Options {
+        let mut options = Options::new(
+            true,
+            false,
+            None,
+            true,
+            Some(BruteForceOptions::build(1, 4, true, false, false).unwrap()),
+            vec![],
+        );
+        options.threads = threads;
+
+        options
+    }
+
+    #[test]
+    fn should_validate_threads() {
+        assert!(matches!(
+            SafeCracker::build(brute_force_options(0)),
+            Err(RamenskyError::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn should_use_potfile() {
+        let path = env::temp_dir().join("ramensky-safe-cracker-potfile-test.txt");
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path);
+
+        let run = || {
+            let mut options = brute_force_options(1);
+            options.potfile_path = Some(path.into());
+            let attempts = AtomicUsize::new(0);
+
+            let result = SafeCracker::build(options)
+                .unwrap()
+                .start(CountingAdaptor {
+                    password: "42",
+                    attempts: &attempts,
+                    fingerprint: Some("test:fingerprint"),
+                })
+                .unwrap();
+
+            match result {
+                PasswordCrackResult::Success(pw, statistics) => {
+                    assert_eq!(pw, "42");
+                    (attempts.into_inner(), statistics)
+                }
+                _ => panic!("Password should have been found."),
+            }
+        };
+
+        let (attempts, _) = run();
+        assert_eq!(attempts, 10 + 43);
+        assert_eq!(
+            Potfile::new(path)
+                .find("test:fingerprint")
+                .unwrap()
+                .unwrap()
+                .password,
+            "42"
+        );
+
+        // The second run finds the password in the potfile, without trying any.
+        let (attempts, statistics) = run();
+        assert_eq!(attempts, 0);
+        assert_eq!(statistics.attempts, 0);
+        assert!(matches!(
+            statistics.found_by,
+            Some(PasswordSource::Potfile { path: potfile_path, .. }) if potfile_path == path
+        ));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_validate_batch_size() {
+        let mut options = brute_force_options(1);
+        options.batch_size = 0;
+
+        assert!(matches!(
+            SafeCracker::build(options),
+            Err(RamenskyError::InvalidConfiguration(_))
+        ));
+    }
+
+    /// Adaptor that only checks passwords in batches, and records the size of every batch.
+    struct BatchAdaptor<'a> {
+        password: &'a str,
+        batches: &'a Mutex<Vec<usize>>,
+    }
+
+    impl<'a> BaseAdaptor for BatchAdaptor<'a> {
+        fn try_password(&self, _: &str) -> Result<AttemptResult, RamenskyError> {
+            panic!("Passwords should be tried in batches.")
+        }
+
+        fn try_passwords(&self, passwords: &[&str]) -> Result<Option<usize>, RamenskyError> {
+            self.batches.lock().unwrap().push(passwords.len());
+            Ok(passwords.iter().position(|pw| *pw == self.password))
+        }
+    }
+
+    #[test]
+    fn should_try_passwords_in_batches() {
+        let mut options = brute_force_options(1);
+        options.batch_size = 100;
+        let safe_cracker = SafeCracker::build(options).unwrap();
+
+        let batches = Mutex::new(vec![]);
+        let adaptor = BatchAdaptor {
+            password: "500",
+            batches: &batches,
+        };
+
+        match safe_cracker.start(adaptor).unwrap() {
+            PasswordCrackResult::Success(pw, statistics) => {
+                assert_eq!(pw, "500");
+                // The passwords after "500" in its batch are not counted.
+                assert_eq!(statistics.attempts, 10 + 100 + 501);
+            }
+            _ => panic!("Password should have been found."),
+        }
+        assert_eq!(batches.into_inner().unwrap(), [100; 7]);
+    }
+
+    #[test]
+    fn should_find_with_multiple_threads() {
+        let attempts = AtomicUsize::new(0);
+        let safe_cracker = SafeCracker::build(brute_force_options(4)).unwrap();
+
+        // "500" is roughly in the middle of the keyspace.
+        let adaptor = CountingAdaptor {
+            password: "500",
+            attempts: &attempts,
+            fingerprint: None,
+        };
+
+        match safe_cracker.start(adaptor).unwrap() {
+            PasswordCrackResult::Success(pw, _) => assert_eq!(pw, "500"),
+            _ => panic!("Password should have been found."),
+        }
+
+        // Every worker stops after the password is found. The other workers can keep going while
+        // the password is being tried, so only check that the keyspace was not exhausted.
+        assert!(attempts.load(Ordering::Relaxed) < 10 + 100 + 1_000 + 10_000);
+    }
+
+    #[test]
+    fn should_try_every_password_with_multiple_threads() {
+        let attempts = AtomicUsize::new(0);
+        let safe_cracker = SafeCracker::build(brute_force_options(3)).unwrap();
+
+        let adaptor = CountingAdaptor {
+            password: "not a number",
+            attempts: &attempts,
+            fingerprint: None,
+        };
+
+        assert!(matches!(
+            safe_cracker.start(adaptor).unwrap(),
+            PasswordCrackResult::Failure(_)
+        ));
+        assert_eq!(attempts.load(Ordering::Relaxed), 10 + 100 + 1_000 + 10_000);
+    }
+
+    #[test]
+    fn should_resume_from_checkpoint() {
+        let checkpoint = Checkpoint {
+            stage: Some(0),
+            stage_attempts: 110,
+            line_offsets: vec![],
+            rule_index: 0,
+            brute_force_index: 110,
+        };
+
+        // "42" is before index 110, so it has already been tried.
+        let safe_cracker = SafeCracker::resume(brute_force_options(1), &checkpoint).unwrap();
+        assert!(matches!(
+            safe_cracker
+                .start(TestAdaptor::without_delay("42"))
+                .unwrap(),
+            PasswordCrackResult::Failure(_)
+        ));
+
+        let attempts = AtomicUsize::new(0);
+        let safe_cracker = SafeCracker::resume(brute_force_options(1), &checkpoint).unwrap();
+        let adaptor = CountingAdaptor {
+            password: "001",
+            attempts: &attempts,
+            fingerprint: None,
+        };
+
+        assert!(matches!(
+            safe_cracker.start(adaptor).unwrap(),
+            PasswordCrackResult::Success(..)
+        ));
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn should_validate_checkpoint() {
+        let checkpoint = Checkpoint {
+            stage: Some(0),
+            stage_attempts: 0,
+            line_offsets: vec![1],
+            rule_index: 0,
+            brute_force_index: 0,
+        };
+        assert!(SafeCracker::resume(brute_force_options(1), &checkpoint).is_err());
+
+        let checkpoint = Checkpoint {
+            stage: Some(1),
+            stage_attempts: 0,
+            line_offsets: vec![],
+            rule_index: 0,
+            brute_force_index: 0,
+        };
+        assert!(SafeCracker::resume(brute_force_options(1), &checkpoint).is_err());
+
+        let checkpoint = Checkpoint {
+            stage: Some(0),
+            stage_attempts: 0,
+            line_offsets: vec![],
+            rule_index: 0,
+            brute_force_index: 1_000_000,
+        };
+        assert!(SafeCracker::resume(brute_force_options(1), &checkpoint).is_err());
+    }
+
+    #[test]
+    fn should_save_checkpoints() {
+        let path = env::temp_dir().join("ramensky-safe-cracker-checkpoint-test.txt");
+        let path = path.to_str().unwrap();
+
+        let mut options = brute_force_options(4);
+        options.checkpoint_path = Some(path.into());
+        options.checkpoint_interval = Duration::ZERO;
+
+        let safe_cracker = SafeCracker::build(options).unwrap();
+        assert!(matches!(
+            safe_cracker
+                .start(TestAdaptor::without_delay("none"))
+                .unwrap(),
+            PasswordCrackResult::Failure(_)
+        ));
+
+        // Every password has been tried.
+        let checkpoint = Checkpoint::load(path).unwrap();
+        assert_eq!(checkpoint.stage, None);
+        assert_eq!(checkpoint.brute_force_index, 0);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_notify_observers() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let observed = Arc::clone(&events);
+
+        let mut options = brute_force_options(2);
+        options.progress_interval = Duration::ZERO;
+
+        let safe_cracker =
+            SafeCracker::build(options)
+                .unwrap()
+                .with_observer(move |event: &Event| {
+                    let name = match event {
+                        Event::Started => "started".to_string(),
+                        Event::StageStarted(stage, attack) => format!("stage {stage} {attack}"),
+                        Event::Attempt(_) => "attempt".to_string(),
+                        Event::Progress(progress) => {
+                            assert!(progress.remaining.is_some());
+                            "progress".to_string()
+                        }
+                        Event::CheckpointSaved(_) => "checkpoint".to_string(),
+                        Event::AdaptorError(_) => "error".to_string(),
+                        Event::Success(pw, _) => format!("success {pw}"),
+                        Event::Failure(_) => "failure".to_string(),
+                        Event::Interrupted(..) => "interrupted".to_string(),
+                    };
+                    observed.lock().unwrap().push(name);
+                });
+
+        assert!(matches!(
+            safe_cracker
+                .start(TestAdaptor::without_delay("42"))
+                .unwrap(),
+            PasswordCrackResult::Success(..)
+        ));
+
+        let events = events.lock().unwrap();
+        let count = |name: &str| events.iter().filter(|event| *event == name).count();
+
+        assert_eq!(events[..2], ["started", "stage 0 brute_force"]);
+        assert_eq!(events.last().unwrap(), "success 42");
+        assert_eq!(count("stage 0 brute_force"), 1);
+        assert_eq!(count("progress"), count("attempt"));
+        assert!(count("attempt") >= 10 + 43);
+    }
+
+    #[test]
+    fn can_start_on_another_thread() {
+        let safe_cracker = SafeCracker::build(brute_force_options(2)).unwrap();
+
+        let result = thread::spawn(move || safe_cracker.start(TestAdaptor::without_delay("42")))
+            .join()
+            .unwrap()
+            .unwrap();
+
+        assert!(matches!(result, PasswordCrackResult::Success(pw, _) if pw == "42"));
+    }
+
+    #[test]
+    fn should_stop_when_cancelled() {
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let safe_cracker = SafeCracker::build(brute_force_options(2))
+            .unwrap()
+            .with_cancellation_token(token.clone());
+
+        match safe_cracker.start(TestAdaptor::without_delay("1")).unwrap() {
+            PasswordCrackResult::Interrupted(checkpoint, statistics) => {
+                assert_eq!(statistics.attempts, 0);
+                assert_eq!(checkpoint.brute_force_index, 0);
+            }
+            _ => panic!("Run should have been interrupted."),
+        }
+    }
+
+    #[test]
+    fn should_stop_after_max_attempts() {
+        let attempts = AtomicUsize::new(0);
+        let mut options = brute_force_options(3);
+        options.max_attempts = Some(50);
+
+        let safe_cracker = SafeCracker::build(options).unwrap();
+        let adaptor = CountingAdaptor {
+            password: "42",
+            attempts: &attempts,
+            fingerprint: None,
+        };
+
+        let checkpoint = match safe_cracker.start(adaptor).unwrap() {
+            PasswordCrackResult::Interrupted(checkpoint, statistics) => {
+                assert_eq!(statistics.attempts, 50);
+                checkpoint
+            }
+            _ => panic!("Run should have been interrupted."),
+        };
+        assert_eq!(attempts.load(Ordering::Relaxed), 50);
+        assert_eq!(checkpoint.brute_force_index, 50);
+
+        // "42" is the 53rd password, so it is found after resuming.
+        let safe_cracker = SafeCracker::resume(brute_force_options(3), &checkpoint).unwrap();
+        assert!(matches!(
+            safe_cracker
+                .start(TestAdaptor::without_delay("42"))
+                .unwrap(),
+            PasswordCrackResult::Success(..)
+        ));
+    }
+
+    #[test]
+    fn should_stop_after_max_duration() {
+        let mut options = brute_force_options(2);
+        options.max_duration = Some(Duration::from_millis(50));
+
+        let safe_cracker = SafeCracker::build(options).unwrap();
+        let adaptor = TestAdaptor::new("none", Duration::from_millis(10));
+
+        match safe_cracker.start(adaptor).unwrap() {
+            PasswordCrackResult::Interrupted(_, statistics) => {
+                assert!(statistics.attempts < 100);
+                assert!(statistics.elapsed < Duration::from_secs(1));
+            }
+            _ => panic!("Run should have been interrupted."),
+        }
+    }
+
+    /// Adaptor that fails with the given error for every password.
+    struct FailingAdaptor {
+        error: fn() -> RamenskyError,
+    }
+
+    impl BaseAdaptor for FailingAdaptor {
+        fn try_password(&self, _: &str) -> Result<AttemptResult, RamenskyError> {
+            Err((self.error)())
+        }
+    }
+
+    #[test]
+    fn should_return_adaptor_errors() {
+        let safe_cracker = SafeCracker::build(brute_force_options(2)).unwrap();
+        let adaptor = FailingAdaptor {
+            error: || RamenskyError::adaptor("Connection refused."),
+        };
+
+        match safe_cracker.start(adaptor) {
+            Err(RamenskyError::Adaptor(e)) => assert_eq!(e.to_string(), "Connection refused."),
+            _ => panic!("Run should have failed with an adaptor error."),
+        }
+    }
+
+    #[test]
+    fn should_interrupt_when_adaptor_cancels() {
+        let safe_cracker = SafeCracker::build(brute_force_options(2)).unwrap();
+        let adaptor = FailingAdaptor {
+            error: || RamenskyError::Cancelled,
+        };
+
+        match safe_cracker.start(adaptor).unwrap() {
+            PasswordCrackResult::Interrupted(_, statistics) => assert_eq!(statistics.attempts, 0),
+            _ => panic!("Run should have been interrupted."),
+        }
+    }
+
+    /// Adaptor that returns an error the first `failures` times the correct password is tried.
+    struct FlakyAdaptor {
+        password: &'static str,
+        failures: AtomicU32,
+    }
+
+    impl FlakyAdaptor {
+        fn new(password: &'static str, failures: u32) -> Self {
+            FlakyAdaptor {
+                password,
+                failures: AtomicU32::new(failures),
+            }
+        }
+    }
+
+    impl BaseAdaptor for FlakyAdaptor {
+        fn try_password(&self, password: &str) -> Result<AttemptResult, RamenskyError> {
+            if password != self.password {
+                return Ok(AttemptResult::Failure);
+            }
+
+            let failed = self
+                .failures
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |f| f.checked_sub(1))
+                .is_ok();
+
+            if failed {
+                Err(RamenskyError::adaptor("Timeout."))
+            } else {
+                Ok(AttemptResult::Success)
+            }
+        }
+    }
+
+    fn error_policy_options(error_policy: ErrorPolicy) -> Options {
+        let mut options = brute_force_options(2);
+        options.error_policy = error_policy;
+
+        options
+    }
+
+    #[test]
+    fn should_skip_errors() {
+        let safe_cracker = SafeCracker::build(error_policy_options(ErrorPolicy::Skip)).unwrap();
+        let adaptor = FailingAdaptor {
+            error: || RamenskyError::adaptor("Connection refused."),
+        };
+
+        match safe_cracker.start(adaptor).unwrap() {
+            PasswordCrackResult::Failure(statistics) => {
+                assert_eq!(statistics.attempts, 10 + 100 + 1_000 + 10_000);
+                assert_eq!(statistics.errors, 10 + 100 + 1_000 + 10_000);
+            }
+            _ => panic!("Every password should have been skipped."),
+        }
+    }
+
+    #[test]
+    fn should_retry_errors() {
+        let error_policy = ErrorPolicy::Retry {
+            retries: 2,
+            backoff: Duration::from_millis(1),
+        };
+
+        let safe_cracker = SafeCracker::build(error_policy_options(error_policy)).unwrap();
+        match safe_cracker.start(FlakyAdaptor::new("42", 2)).unwrap() {
+            PasswordCrackResult::Success(pw, statistics) => {
+                assert_eq!(pw, "42");
+                assert_eq!(statistics.errors, 2);
+                // The other worker keeps trying passwords while "42" is retried.
+                assert!(statistics.attempts >= 10 + 43);
+            }
+            _ => panic!("Password should have been found."),
+        }
+
+        // The password fails once more than it is retried.
+        let safe_cracker = SafeCracker::build(error_policy_options(error_policy)).unwrap();
+        assert!(matches!(
+            safe_cracker.start(FlakyAdaptor::new("42", 3)),
+            Err(RamenskyError::Adaptor(_))
+        ));
+    }
+
+    #[test]
+    fn should_requeue_errors() {
+        let error_policy = ErrorPolicy::Requeue {
+            pause: Duration::ZERO,
+            max_requeues: 2,
+        };
+
+        let safe_cracker = SafeCracker::build(error_policy_options(error_policy)).unwrap();
+        match safe_cracker.start(FlakyAdaptor::new("9999", 2)).unwrap() {
+            PasswordCrackResult::Success(pw, statistics) => {
+                assert_eq!(pw, "9999");
+                assert_eq!(statistics.errors, 2);
+            }
+            _ => panic!("Password should have been found."),
+        }
+
+        let safe_cracker = SafeCracker::build(error_policy_options(error_policy)).unwrap();
+        assert!(matches!(
+            safe_cracker.start(FlakyAdaptor::new("9999", 3)),
+            Err(RamenskyError::Adaptor(_))
+        ));
+    }
+
+    #[test]
+    fn should_collect_statistics() {
+        let safe_cracker = SafeCracker::build(brute_force_options(1)).unwrap();
+
+        let statistics = match safe_cracker
+            .start(TestAdaptor::without_delay("42"))
+            .unwrap()
+        {
+            PasswordCrackResult::Success(_, statistics) => statistics,
+            _ => panic!("Password should have been found."),
+        };
+
+        assert_eq!(statistics.attempts, 10 + 43);
+        assert_eq!(statistics.stage_attempts, [10 + 43]);
+        assert_eq!(statistics.errors, 0);
+        assert_eq!(
+            statistics.found_by,
+            Some(PasswordSource::BruteForce {
+                stage: 0,
+                index: 10 + 42
+            })
+        );
+        assert!(statistics.started_at <= statistics.finished_at);
+        assert!(statistics.peak_attempts_per_second >= statistics.average_attempts_per_second);
+    }
 }
@@ -0,0 +1,114 @@
+//! Module containing the statistics of a `SafeCracker` run.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+
+/// Statistics of a `SafeCracker` run, returned as part of the
+/// [`PasswordCrackResult`](crate::safe_cracker::password_crack_result::PasswordCrackResult).
+#[derive(Clone, Debug)]
+pub struct Statistics {
+    /// When the run was started.
+    pub started_at: DateTime<Local>,
+
+    /// When the run was finished.
+    pub finished_at: DateTime<Local>,
+
+    /// Duration of the execution.
+    pub elapsed: Duration,
+
+    /// Amount of passwords tried. Passwords that were retried or requeued are only counted once.
+    pub attempts: u64,
+
+    /// Amount of passwords tried in every stage of the
+    /// [`pipeline`](crate::safe_cracker::options::Options::pipeline), in order.
+    pub stage_attempts: Vec<u64>,
+
+    /// Amount of errors returned by the adaptor, including the ones that were retried or skipped.
+    pub errors: u64,
+
+    /// Average amount of passwords tried per second.
+    pub average_attempts_per_second: f64,
+
+    /// Highest amount of passwords tried per second, measured every
+    /// [`progress_interval`](crate::safe_cracker::options::Options::progress_interval).
+    pub peak_attempts_per_second: f64,
+
+    /// Amount of password list lines that were skipped, because they were not valid UTF-8.
+    /// See [`EncodingPolicy`](crate::safe_cracker::encoding_policy::EncodingPolicy). Also counts the
+    /// passwords that were not valid UTF-8, if the adaptor only takes strings, see
+    /// [`ByteAdaptor::accepts_raw_passwords()`](crate::adaptor::byte_adaptor::ByteAdaptor::accepts_raw_passwords).
+    pub skipped_lines: u64,
+
+    /// Amount of password list lines that were converted to UTF-8, because they were not valid UTF-8.
+    pub converted_lines: u64,
+
+    /// Where the password came from, if it was found.
+    pub found_by: Option<PasswordSource>,
+}
+
+impl Statistics {
+    /// Statistics of a run that did not try any passwords.
+    pub(crate) fn empty(started_at: DateTime<Local>) -> Self {
+        Statistics {
+            started_at,
+            finished_at: Local::now(),
+            elapsed: Duration::ZERO,
+            attempts: 0,
+            stage_attempts: vec![],
+            errors: 0,
+            average_attempts_per_second: 0.0,
+            peak_attempts_per_second: 0.0,
+            skipped_lines: 0,
+            converted_lines: 0,
+            found_by: None,
+        }
+    }
+
+    /// Amount of passwords tried in the stage at the given index of the pipeline.
+    pub fn attempts_in(&self, stage: usize) -> u64 {
+        self.stage_attempts.get(stage).copied().unwrap_or(0)
+    }
+}
+
+/// Where a password came from. `stage` is the index of the stage in the pipeline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PasswordSource {
+    /// A line of a password list. Lines start at 1, and count every line of the file.
+    /// If rules are used, `rule_index` is the index of the rule that was applied to the line.
+    PasswordList {
+        stage: usize,
+        path: String,
+        line: usize,
+        rule_index: Option<usize>,
+    },
+
+    /// A line of the `left` list of a combinator, followed by a line of its `right` list.
+    Combinator {
+        stage: usize,
+        path: String,
+        line: usize,
+        right_path: String,
+        right_line: usize,
+    },
+
+    /// A line of the password list of a hybrid attack, combined with the password at `mask_index`
+    /// in the keyspace of its mask.
+    Hybrid {
+        stage: usize,
+        path: String,
+        line: usize,
+        mask_index: u128,
+    },
+
+    /// An index in the brute forcing keyspace, or the keyspace of a mask, see
+    /// [`BruteForcer::password_at()`](crate::safe_cracker::brute_forcer::BruteForcer::password_at).
+    BruteForce { stage: usize, index: u128 },
+
+    /// The [`potfile`](crate::safe_cracker::options::Options::potfile_path), as the password was found
+    /// by a previous run at `found_at`.
+    Potfile {
+        path: String,
+        found_at: DateTime<Local>,
+    },
+}